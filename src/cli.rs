@@ -0,0 +1,174 @@
+use crate::{Board, GameState};
+use std::fmt;
+use std::io::{self, BufRead, Write};
+
+const USAGE: &str =
+    "commands: r[eveal] <row> <col>, f[lag] <row> <col>, c[hord] <row> <col>, q[uit]";
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    Reveal(usize, usize),
+    Flag(usize, usize),
+    Chord(usize, usize),
+    Quit,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CommandError {
+    Empty,
+    UnknownCommand(String),
+    MissingArgument,
+    ExtraArgument(String),
+    NotANumber(String),
+    OutOfRange(usize, usize),
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "no command given"),
+            Self::UnknownCommand(command) => write!(f, "unknown command '{}'", command),
+            Self::MissingArgument => write!(f, "expected a row and a column"),
+            Self::ExtraArgument(argument) => write!(f, "unexpected argument '{}'", argument),
+            Self::NotANumber(argument) => write!(f, "'{}' isn't a number", argument),
+            Self::OutOfRange(row, col) => write!(f, "({}, {}) is off the board", row, col),
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+// Parses one line of input for a board of the given size.
+pub fn parse_command(line: &str, width: usize, height: usize) -> Result<Command, CommandError> {
+    let mut parts = line.split_whitespace();
+
+    let command = parts.next().ok_or(CommandError::Empty)?;
+    let command: fn(usize, usize) -> Command = match command {
+        "r" | "reveal" => Command::Reveal,
+        "f" | "flag" => Command::Flag,
+        "c" | "chord" => Command::Chord,
+        "q" | "quit" => {
+            return match parts.next() {
+                Some(extra) => Err(CommandError::ExtraArgument(extra.to_string())),
+                None => Ok(Command::Quit),
+            };
+        }
+        _ => return Err(CommandError::UnknownCommand(command.to_string())),
+    };
+
+    let mut coordinate = || -> Result<usize, CommandError> {
+        let part = parts.next().ok_or(CommandError::MissingArgument)?;
+        part.parse()
+            .map_err(|_| CommandError::NotANumber(part.to_string()))
+    };
+    let row = coordinate()?;
+    let col = coordinate()?;
+
+    if let Some(extra) = parts.next() {
+        return Err(CommandError::ExtraArgument(extra.to_string()));
+    }
+    if row >= height || col >= width {
+        return Err(CommandError::OutOfRange(row, col));
+    }
+
+    Ok(command(row, col))
+}
+
+// Plays `board` in the terminal, reading one command per line from `input` and printing the board
+// to `output` after every move, until the game is won or lost, the player quits or the input runs
+// out.
+pub fn run<R: BufRead, W: Write>(board: &mut Board, input: R, mut output: W) -> io::Result<()> {
+    writeln!(output, "{}", USAGE)?;
+    writeln!(output, "{:#}", board)?;
+
+    for line in input.lines() {
+        let line = line?;
+
+        match parse_command(&line, board.width(), board.height()) {
+            Ok(Command::Reveal(row, col)) => {
+                board.reveal_cell(row, col);
+            }
+            Ok(Command::Flag(row, col)) => {
+                board.flag_cell(row, col);
+            }
+            Ok(Command::Chord(row, col)) => board.chord_cell(row, col),
+            Ok(Command::Quit) => break,
+            Err(CommandError::Empty) => continue,
+            Err(err) => {
+                writeln!(output, "{}", err)?;
+                writeln!(output, "{}", USAGE)?;
+                continue;
+            }
+        }
+
+        writeln!(output, "{:#}", board)?;
+
+        match board.state() {
+            GameState::Won => {
+                writeln!(output, "You win!")?;
+                break;
+            }
+            GameState::Lost => {
+                writeln!(output, "Game over!")?;
+                break;
+            }
+            GameState::Playing => {}
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_commands_and_their_long_names() {
+        assert_eq!(parse_command("r 1 2", 4, 3), Ok(Command::Reveal(1, 2)));
+        assert_eq!(parse_command("reveal 0 3", 4, 3), Ok(Command::Reveal(0, 3)));
+        assert_eq!(parse_command("  f 2 0 ", 4, 3), Ok(Command::Flag(2, 0)));
+        assert_eq!(parse_command("flag 2 0", 4, 3), Ok(Command::Flag(2, 0)));
+        assert_eq!(parse_command("c 1 1", 4, 3), Ok(Command::Chord(1, 1)));
+        assert_eq!(parse_command("chord 1 1", 4, 3), Ok(Command::Chord(1, 1)));
+        assert_eq!(parse_command("q", 4, 3), Ok(Command::Quit));
+        assert_eq!(parse_command("quit", 4, 3), Ok(Command::Quit));
+    }
+
+    #[test]
+    fn rejects_malformed_commands() {
+        assert_eq!(parse_command("   ", 4, 3), Err(CommandError::Empty));
+        assert_eq!(
+            parse_command("x 1 1", 4, 3),
+            Err(CommandError::UnknownCommand("x".to_string()))
+        );
+        assert_eq!(
+            parse_command("r 1", 4, 3),
+            Err(CommandError::MissingArgument)
+        );
+        assert_eq!(
+            parse_command("r 1 a", 4, 3),
+            Err(CommandError::NotANumber("a".to_string()))
+        );
+        assert_eq!(
+            parse_command("f -1 0", 4, 3),
+            Err(CommandError::NotANumber("-1".to_string()))
+        );
+        assert_eq!(
+            parse_command("r 1 1 1", 4, 3),
+            Err(CommandError::ExtraArgument("1".to_string()))
+        );
+        assert_eq!(
+            parse_command("q now", 4, 3),
+            Err(CommandError::ExtraArgument("now".to_string()))
+        );
+        assert_eq!(
+            parse_command("r 3 0", 4, 3),
+            Err(CommandError::OutOfRange(3, 0))
+        );
+        assert_eq!(
+            parse_command("r 0 4", 4, 3),
+            Err(CommandError::OutOfRange(0, 4))
+        );
+    }
+}
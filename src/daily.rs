@@ -0,0 +1,85 @@
+use crate::{Board, Difficulty};
+#[cfg(feature = "std")]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "std")]
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+// The seed shared by everyone playing on a given day, e.g. 20200614 for 14 June 2020, so players
+// can check they had the same board by comparing seeds.
+pub fn daily_seed(year: u32, month: u32, day: u32) -> u64 {
+    u64::from(year) * 10_000 + u64::from(month) * 100 + u64::from(day)
+}
+
+// Today's date in UTC as (year, month, day).
+#[cfg(feature = "std")]
+pub fn today() -> (u32, u32, u32) {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    civil_from_days(seconds / SECONDS_PER_DAY)
+}
+
+// Converts days since 1970-01-01 to a Gregorian date, following Howard Hinnant's
+// `civil_from_days`. Only dates after the epoch are needed, so everything stays unsigned.
+#[cfg(feature = "std")]
+fn civil_from_days(days: u64) -> (u32, u32, u32) {
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year as u32, month as u32, day as u32)
+}
+
+impl Board {
+    // Today's puzzle at the given difficulty, the same for everyone.
+    #[cfg(feature = "std")]
+    pub fn daily(difficulty: Difficulty) -> Self {
+        let (year, month, day) = today();
+        Self::daily_on(difficulty, year, month, day)
+    }
+
+    pub fn daily_on(difficulty: Difficulty, year: u32, month: u32, day: u32) -> Self {
+        let (width, height, mines) = difficulty.parameters();
+        Self::new_seeded(width, height, mines, daily_seed(year, month, day))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn daily_board_is_the_same_for_a_date() {
+        let mut first = Board::daily_on(Difficulty::Intermediate, 2020, 6, 14);
+        let mut second = Board::daily_on(Difficulty::Intermediate, 2020, 6, 14);
+        assert_eq!(first.seed(), 20_200_614);
+        assert_eq!(second.seed(), first.seed());
+
+        first.reveal_cell(8, 8);
+        second.reveal_cell(8, 8);
+        assert_eq!(first, second);
+
+        let next_day = Board::daily_on(Difficulty::Intermediate, 2020, 6, 15);
+        assert_ne!(next_day.seed(), first.seed());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn converts_days_to_dates() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(18_427), (2020, 6, 14));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+    }
+}
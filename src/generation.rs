@@ -0,0 +1,293 @@
+#[cfg(feature = "std")]
+use crate::BoardError;
+use crate::{select_random_coords, Board};
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use rand::rngs::StdRng;
+use rand::RngCore;
+#[cfg(feature = "std")]
+use rand::{thread_rng, Rng, SeedableRng};
+#[cfg(feature = "serde")]
+use serde_crate::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(crate = "serde_crate", default))]
+pub struct GenerationOptions {
+    // The most mines allowed in any 3x3 window, or `None` to accept any layout.
+    pub max_mines_per_window: Option<usize>,
+    // How many extra layouts to try before settling for one that breaks the limit.
+    pub retries: usize,
+    // How many steps out from the first reveal are kept free of mines: 0 for just the cell, 1 for
+    // its neighbours too, and so on.
+    pub first_click_safe_radius: usize,
+}
+
+impl Default for GenerationOptions {
+    fn default() -> Self {
+        Self {
+            max_mines_per_window: None,
+            retries: 100,
+            first_click_safe_radius: 1,
+        }
+    }
+}
+
+// Chooses where a board's mines go, as (row, col) coordinates.
+pub trait MinePlacer {
+    fn place(
+        &self,
+        width: usize,
+        height: usize,
+        count: usize,
+        rng: &mut dyn RngCore,
+    ) -> Vec<(usize, usize)>;
+}
+
+// Every cell is equally likely to hold a mine.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct UniformPlacer;
+
+impl MinePlacer for UniformPlacer {
+    fn place(
+        &self,
+        width: usize,
+        height: usize,
+        count: usize,
+        rng: &mut dyn RngCore,
+    ) -> Vec<(usize, usize)> {
+        let mut coords: Vec<(usize, usize)> = (0..height)
+            .flat_map(|row| (0..width).map(move |col| (row, col)))
+            .collect();
+        select_random_coords(&mut coords, count, rng).to_vec()
+    }
+}
+
+impl Board {
+    // Builds a board with its mines placed up front by `placer`. Unlike `new`, the first reveal
+    // isn't guaranteed to be safe.
+    #[cfg(feature = "std")]
+    pub fn new_with_placer(
+        width: usize,
+        height: usize,
+        mines: usize,
+        placer: &dyn MinePlacer,
+    ) -> Result<Self, BoardError> {
+        let seed = thread_rng().gen();
+        let mut rng = StdRng::seed_from_u64(seed);
+        let positions = placer.place(width, height, mines, &mut rng);
+
+        let mut board = Self::with_mines(width, height, &positions)?;
+        board.seed = seed;
+        Ok(board)
+    }
+
+    pub fn with_generation_options(mut self, generation: GenerationOptions) -> Self {
+        self.generation = generation;
+        self
+    }
+
+    pub fn generation_options(&self) -> GenerationOptions {
+        self.generation
+    }
+
+    // The cells within `first_click_safe_radius` steps of (row, col), starting with the cell itself.
+    pub(crate) fn first_click_safe_area(&self, row: usize, col: usize) -> Vec<(usize, usize)> {
+        let mut safe = vec![(row, col)];
+        let mut frontier = safe.clone();
+
+        for _ in 0..self.generation.first_click_safe_radius {
+            // A huge radius would otherwise keep looping long after the whole board is covered.
+            if frontier.is_empty() {
+                break;
+            }
+            let mut next = Vec::new();
+            for &(row, col) in &frontier {
+                for cell in self.neighbours(row, col) {
+                    if !safe.contains(&cell) {
+                        safe.push(cell);
+                        next.push(cell);
+                    }
+                }
+            }
+            frontier = next;
+        }
+
+        safe
+    }
+
+    // Whether a candidate layout of `(col, row)` mine coordinates keeps every 3x3 window, centred
+    // on each cell, within `max_mines_per_window`. Windows are plain squares clipped to the board,
+    // whatever the connectivity or topology.
+    pub(crate) fn within_window_limit(&self, mines: &[(usize, usize)]) -> bool {
+        let max = match self.generation.max_mines_per_window {
+            Some(max) => max,
+            None => return true,
+        };
+
+        let mut is_mine = vec![vec![false; self.width()]; self.height()];
+        for &(col, row) in mines {
+            is_mine[row][col] = true;
+        }
+
+        (0..self.height()).all(|row| {
+            (0..self.width()).all(|col| {
+                let rows = row.saturating_sub(1)..=(row + 1).min(self.height() - 1);
+                let window = rows
+                    .flat_map(|o_row| {
+                        let cols = col.saturating_sub(1)..=(col + 1).min(self.width() - 1);
+                        cols.map(move |o_col| (o_row, o_col))
+                    })
+                    .filter(|&(o_row, o_col)| is_mine[o_row][o_col])
+                    .count();
+                window <= max
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CellState, Connectivity, Marker};
+
+    fn limited(max: usize) -> GenerationOptions {
+        GenerationOptions {
+            max_mines_per_window: Some(max),
+            ..GenerationOptions::default()
+        }
+    }
+
+    fn safe_radius(radius: usize) -> GenerationOptions {
+        GenerationOptions {
+            first_click_safe_radius: radius,
+            ..GenerationOptions::default()
+        }
+    }
+
+    #[test]
+    fn window_limit_counts_diagonals_with_four_connectivity() {
+        let board = Board::new_seeded(5, 5, 4, 0)
+            .with_connectivity(Connectivity::Four)
+            .with_generation_options(limited(3));
+
+        // A mine in each corner of the window centred on (1, 1), none of them orthogonal to it.
+        let corners = [(0, 0), (2, 0), (0, 2), (2, 2)];
+        assert!(!board.within_window_limit(&corners));
+        assert!(board.within_window_limit(&corners[..3]));
+    }
+
+    #[test]
+    fn placed_mines_respect_the_window_limit() {
+        let mut board = Board::new_seeded(9, 9, 10, 5).with_generation_options(limited(2));
+        board.reveal_cell(4, 4);
+
+        let mines: Vec<(usize, usize)> = board
+            .iter_cells()
+            .filter(|(_, _, cell)| matches!(cell, CellState::Mine(..)))
+            .map(|(row, col, _)| (col, row))
+            .collect();
+        assert_eq!(mines.len(), 10);
+        assert!(board.within_window_limit(&mines));
+    }
+
+    // Always puts the mines in the same places, ignoring the count and the RNG.
+    #[cfg(feature = "std")]
+    struct FixedPlacer(Vec<(usize, usize)>);
+
+    #[cfg(feature = "std")]
+    impl MinePlacer for FixedPlacer {
+        fn place(&self, _: usize, _: usize, _: usize, _: &mut dyn RngCore) -> Vec<(usize, usize)> {
+            self.0.clone()
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn board_uses_exactly_the_placed_mines() {
+        let placer = FixedPlacer(vec![(3, 1), (0, 4), (2, 2)]);
+        let board = Board::new_with_placer(5, 4, 3, &placer).unwrap();
+
+        assert!(board.mines_placed());
+        assert_eq!(board.mine_count(), 3);
+        assert_eq!(board.mine_positions(), vec![(0, 4), (2, 2), (3, 1)]);
+        assert_eq!(
+            board.cell(2, 1),
+            CellState::Neighbours(false, Marker::None, 2)
+        );
+
+        let placer = FixedPlacer(vec![(4, 0)]);
+        assert_eq!(
+            Board::new_with_placer(5, 4, 1, &placer),
+            Err(BoardError::MineOutOfBounds(4, 0))
+        );
+    }
+
+    #[test]
+    fn safe_area_grows_with_the_radius() {
+        let board = Board::new_seeded(5, 5, 4, 0).with_generation_options(safe_radius(0));
+        assert_eq!(board.first_click_safe_area(2, 2), vec![(2, 2)]);
+
+        let board = board.with_generation_options(safe_radius(1));
+        let mut area = board.first_click_safe_area(2, 2);
+        area.sort();
+        let expected: Vec<(usize, usize)> = (1..=3)
+            .flat_map(|row| (1..=3).map(move |col| (row, col)))
+            .collect();
+        assert_eq!(area, expected);
+
+        let mut corner = board.first_click_safe_area(0, 0);
+        corner.sort();
+        assert_eq!(corner, vec![(0, 0), (0, 1), (1, 0), (1, 1)]);
+    }
+
+    #[test]
+    fn huge_safe_radius_stops_at_the_edges() {
+        let board = Board::new_seeded(4, 3, 1, 0).with_generation_options(safe_radius(usize::MAX));
+        assert_eq!(board.first_click_safe_area(1, 1).len(), 12);
+    }
+
+    #[test]
+    fn first_reveal_clears_the_neighbours_with_radius_one() {
+        for seed in 0..20 {
+            let mut board =
+                Board::new_seeded(5, 5, 10, seed).with_generation_options(safe_radius(1));
+            board.reveal_cell(2, 2);
+
+            let mines = board.mine_positions();
+            assert_eq!(mines.len(), 10);
+            for row in 1..=3 {
+                for col in 1..=3 {
+                    assert!(!mines.contains(&(row, col)), "seed {}", seed);
+                }
+            }
+        }
+
+        // With no room elsewhere, the mines fill everything outside the clicked cell's neighbours.
+        let mut full = Board::new_seeded(5, 5, 16, 3).with_generation_options(safe_radius(1));
+        full.reveal_cell(2, 2);
+        let ring: Vec<(usize, usize)> = (0..5)
+            .flat_map(|row| (0..5).map(move |col| (row, col)))
+            .filter(|&(row, col)| row == 0 || row == 4 || col == 0 || col == 4)
+            .collect();
+        assert_eq!(full.mine_positions(), ring);
+    }
+
+    #[test]
+    fn first_reveal_only_clears_the_clicked_cell_with_radius_zero() {
+        for seed in 0..20 {
+            let mut board =
+                Board::new_seeded(5, 5, 24, seed).with_generation_options(safe_radius(0));
+            board.reveal_cell(2, 2);
+
+            let mines = board.mine_positions();
+            assert_eq!(mines.len(), 24);
+            assert!(!mines.contains(&(2, 2)));
+            assert_eq!(
+                board.cell(2, 2),
+                CellState::Neighbours(true, Marker::None, 8)
+            );
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use crate::{Board, BoardError};
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::path::Path;
+
+impl Board {
+    pub fn save_json<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let file = File::create(path)?;
+        serde_json::to_writer(BufWriter::new(file), &*self.saved())?;
+        Ok(())
+    }
+
+    // The board as it should be written out: a peek isn't part of the game, and the grid it hides
+    // isn't serialized, so a peeking board is saved with its cells covered again.
+    fn saved(&self) -> Cow<'_, Self> {
+        if self.is_peeking() {
+            let mut board = self.clone();
+            board.hide_all();
+            Cow::Owned(board)
+        } else {
+            Cow::Borrowed(self)
+        }
+    }
+
+    // Loads a board saved by `save_json`, rejecting files that don't describe a valid board.
+    pub fn load_json<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let board: Self = serde_json::from_reader(BufReader::new(file))?;
+
+        Self::from_loaded(board).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    // Accepts a board read from a file once `check_loaded` passes. The neighbour counts follow
+    // from the mines, so they're worked out again rather than trusted.
+    fn from_loaded(mut board: Self) -> Result<Self, BoardError> {
+        board.check_loaded()?;
+        board.recount_neighbours();
+        Ok(board)
+    }
+
+    // What `load_json` checks of a board read from a file, which could say anything: the grid has
+    // to match the size, and the mine count the rules `repr` holds boards to.
+    fn check_loaded(&self) -> Result<(), BoardError> {
+        if self.width == 0 || self.height == 0 {
+            return Err(BoardError::ZeroDimension);
+        }
+        let cells = self
+            .width
+            .checked_mul(self.height)
+            .ok_or(BoardError::BadDimensions)?;
+        if self.grid.len() != cells {
+            return Err(BoardError::RaggedGrid);
+        }
+        if self.mines >= cells {
+            return Err(BoardError::TooManyMines);
+        }
+        if self.mines_placed && self.mine_positions().len() != self.mines {
+            return Err(BoardError::MineCountMismatch);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{board, TempFile};
+    use crate::{Board, BoardError, CellState, Marker};
+
+    #[test]
+    fn round_trips_through_a_file() {
+        let mut board = board(&["*..", "...", "..*"]);
+        board.reveal_cell(0, 2);
+        board.flag_cell(0, 0);
+
+        let file = TempFile::new("round-trip");
+        board.save_json(&file.0).unwrap();
+        let loaded = Board::load_json(&file.0).unwrap();
+
+        assert_eq!(loaded, board);
+        assert_eq!(loaded.seed(), board.seed());
+    }
+
+    #[test]
+    fn loaded_boards_need_a_grid_that_fits() {
+        let mut zero = board(&["*..", "..."]);
+        zero.width = 0;
+        assert_eq!(zero.check_loaded(), Err(BoardError::ZeroDimension));
+
+        let mut empty = board(&["*..", "..."]);
+        empty.grid.clear();
+        assert_eq!(empty.check_loaded(), Err(BoardError::RaggedGrid));
+
+        let mut short = board(&["*..", "..."]);
+        short.height = 3;
+        assert_eq!(short.check_loaded(), Err(BoardError::RaggedGrid));
+
+        let mut huge = board(&["*..", "..."]);
+        huge.width = usize::MAX;
+        assert_eq!(huge.check_loaded(), Err(BoardError::BadDimensions));
+
+        assert_eq!(board(&["*..", "..."]).check_loaded(), Ok(()));
+    }
+
+    #[test]
+    fn loaded_boards_get_their_counts_recomputed() {
+        let expected = board(&["*..", "..."]);
+        let mut tampered = expected.clone();
+        tampered.grid[1] = CellState::Neighbours(false, Marker::None, 7);
+
+        assert_eq!(Board::from_loaded(tampered), Ok(expected));
+    }
+
+    #[test]
+    fn loaded_boards_need_a_consistent_mine_count() {
+        let mut full = board(&["*..", "..."]);
+        full.mines = 6;
+        assert_eq!(full.check_loaded(), Err(BoardError::TooManyMines));
+
+        let mut miscounted = board(&["*..", "..."]);
+        miscounted.mines = 2;
+        assert_eq!(
+            miscounted.check_loaded(),
+            Err(BoardError::MineCountMismatch)
+        );
+
+        // Until the first reveal places them, the count is all there is.
+        let mut unplaced = Board::new_seeded(3, 2, 5, 0);
+        assert_eq!(unplaced.check_loaded(), Ok(()));
+        unplaced.mines = 6;
+        assert_eq!(unplaced.check_loaded(), Err(BoardError::TooManyMines));
+    }
+
+    #[test]
+    fn peeking_boards_are_saved_hidden() {
+        let mut board = board(&["*..", "...", "..*"]);
+        board.reveal_cell(0, 2);
+        let hidden = board.clone();
+
+        board.reveal_all();
+        let saved = board.saved();
+        assert!(!saved.is_peeking());
+        assert_eq!(*saved, hidden);
+        assert!(board.is_peeking());
+
+        let file = TempFile::new("peeking");
+        board.save_json(&file.0).unwrap();
+        assert_eq!(Board::load_json(&file.0).unwrap(), hidden);
+    }
+}
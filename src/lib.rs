@@ -0,0 +1,2722 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::collections::VecDeque;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::fmt;
+use rand::rngs::StdRng;
+#[cfg(feature = "std")]
+use rand::thread_rng;
+use rand::{Rng, SeedableRng};
+
+#[cfg(feature = "serde")]
+use serde_crate::{Deserialize, Serialize};
+
+#[cfg(feature = "std")]
+pub mod cli;
+mod daily;
+mod generation;
+#[cfg(feature = "serde")]
+mod json;
+mod replay;
+mod repr;
+#[cfg(feature = "serde")]
+mod scores;
+mod solver;
+mod sound;
+
+pub use daily::daily_seed;
+#[cfg(feature = "std")]
+pub use daily::today;
+pub use generation::{GenerationOptions, MinePlacer, UniformPlacer};
+pub use replay::Move;
+pub use repr::ParseError;
+#[cfg(feature = "serde")]
+pub use scores::Scores;
+pub use sound::{Silent, SoundSink};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(crate = "serde_crate"))]
+pub enum Marker {
+    None,
+    Flag,
+    Question,
+}
+
+impl Marker {
+    fn next(self) -> Self {
+        match self {
+            Self::None => Self::Flag,
+            Self::Flag => Self::Question,
+            Self::Question => Self::None,
+        }
+    }
+}
+
+// Mine(revealed, marker), Neighbours(revealed, marker, count)
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(crate = "serde_crate"))]
+pub enum CellState {
+    Mine(bool, Marker),
+    Neighbours(bool, Marker, u8),
+}
+
+fn is_flagged(cell: &CellState) -> bool {
+    matches!(
+        cell,
+        CellState::Mine(_, Marker::Flag) | CellState::Neighbours(_, Marker::Flag, _)
+    )
+}
+
+fn is_revealed(cell: &CellState) -> bool {
+    matches!(
+        cell,
+        CellState::Mine(true, _) | CellState::Neighbours(true, _, _)
+    )
+}
+
+// The orthogonal directions come first, see `Connectivity::Four`.
+pub const ALL_DIRECTIONS: [Direction; 8] = [
+    Direction::N,
+    Direction::E,
+    Direction::S,
+    Direction::W,
+    Direction::NE,
+    Direction::SE,
+    Direction::SW,
+    Direction::NW,
+];
+
+pub enum Direction {
+    N,
+    E,
+    S,
+    W,
+    NE,
+    SE,
+    SW,
+    NW,
+}
+
+impl Direction {
+    // Rows grow to the south and columns to the east. Only underflow is guarded against here, so
+    // callers still need to check the result with `in_bounds`.
+    pub fn offset(&self, (row, col): (usize, usize)) -> Option<(usize, usize)> {
+        match self {
+            Self::N if row > 0 => Some((row - 1, col)),
+            Self::E => Some((row, col + 1)),
+            Self::S => Some((row + 1, col)),
+            Self::W if col > 0 => Some((row, col - 1)),
+            Self::NE if row > 0 => Some((row - 1, col + 1)),
+            Self::SE => Some((row + 1, col + 1)),
+            Self::SW if col > 0 => Some((row + 1, col - 1)),
+            Self::NW if row > 0 && col > 0 => Some((row - 1, col - 1)),
+            _ => None,
+        }
+    }
+
+    // Like `offset`, but wraps around the edges of a `width` x `height` board.
+    pub fn wrapping_offset(
+        &self,
+        (row, col): (usize, usize),
+        width: usize,
+        height: usize,
+    ) -> (usize, usize) {
+        let (d_row, d_col) = self.delta();
+        (
+            (row as isize + d_row).rem_euclid(height as isize) as usize,
+            (col as isize + d_col).rem_euclid(width as isize) as usize,
+        )
+    }
+
+    fn delta(&self) -> (isize, isize) {
+        match self {
+            Self::N => (-1, 0),
+            Self::E => (0, 1),
+            Self::S => (1, 0),
+            Self::W => (0, -1),
+            Self::NE => (-1, 1),
+            Self::SE => (1, 1),
+            Self::SW => (1, -1),
+            Self::NW => (-1, -1),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(crate = "serde_crate"))]
+pub enum Topology {
+    Flat,
+    // The left and right edges are adjacent, as are the top and bottom.
+    Torus,
+}
+
+impl Default for Topology {
+    fn default() -> Self {
+        Self::Flat
+    }
+}
+
+impl fmt::Display for CellState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Mine(_, Marker::Flag) | Self::Neighbours(false, Marker::Flag, _) => {
+                write!(f, "!")
+            }
+            Self::Mine(true, _) => write!(f, "X"),
+            Self::Neighbours(true, _, count) => write!(f, "{}", count),
+            Self::Mine(false, Marker::Question) | Self::Neighbours(false, Marker::Question, _) => {
+                write!(f, "?")
+            }
+            _ => write!(f, " "),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(crate = "serde_crate"))]
+pub enum GameState {
+    Playing,
+    Won,
+    Lost,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(crate = "serde_crate"))]
+pub enum Difficulty {
+    Beginner,
+    Intermediate,
+    Expert,
+}
+
+impl Difficulty {
+    // (width, height, mines)
+    pub fn parameters(&self) -> (usize, usize, usize) {
+        match self {
+            Self::Beginner => (9, 9, 10),
+            Self::Intermediate => (16, 16, 40),
+            Self::Expert => (30, 16, 99),
+        }
+    }
+
+    // The next difficulty up, wrapping round from expert to beginner.
+    pub fn next(self) -> Self {
+        match self {
+            Self::Beginner => Self::Intermediate,
+            Self::Intermediate => Self::Expert,
+            Self::Expert => Self::Beginner,
+        }
+    }
+
+    // The difficulty a board of this size was made for, if any.
+    pub fn from_parameters(width: usize, height: usize, mines: usize) -> Option<Self> {
+        [Self::Beginner, Self::Intermediate, Self::Expert]
+            .iter()
+            .copied()
+            .find(|difficulty| difficulty.parameters() == (width, height, mines))
+    }
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Self::Beginner
+    }
+}
+
+impl core::str::FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "beginner" => Ok(Self::Beginner),
+            "intermediate" => Ok(Self::Intermediate),
+            "expert" => Ok(Self::Expert),
+            _ => Err(format!(
+                "unknown difficulty '{}', expected beginner, intermediate or expert",
+                s
+            )),
+        }
+    }
+}
+
+// Which cells count as neighbours, for mine counts as well as flood fill and chording.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(crate = "serde_crate"))]
+pub enum Connectivity {
+    Eight,
+    // Only N, E, S and W; diagonal mines don't count towards a cell's number.
+    Four,
+}
+
+impl Default for Connectivity {
+    fn default() -> Self {
+        Self::Eight
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(crate = "serde_crate"))]
+pub enum WinCondition {
+    AllSafeRevealed,
+    // Flags have to cover exactly the mines; revealing every safe cell isn't enough.
+    AllMinesFlagged,
+}
+
+impl Default for WinCondition {
+    fn default() -> Self {
+        Self::AllSafeRevealed
+    }
+}
+
+// How far a flood from a revealed zero spreads.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(crate = "serde_crate"))]
+pub enum FloodStyle {
+    // The zeros and the numbers bordering them.
+    IncludeBorder,
+    // Only the zeros; the numbers around them stay hidden.
+    ZeroOnly,
+}
+
+impl Default for FloodStyle {
+    fn default() -> Self {
+        Self::IncludeBorder
+    }
+}
+
+// What a chord does when the flags around the number are wrong.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(crate = "serde_crate"))]
+pub enum ChordSafety {
+    // Reveals the unflagged neighbours anyway, setting off any mine among them.
+    Strict,
+    // Does nothing unless every flag is on a mine.
+    Safe,
+}
+
+impl Default for ChordSafety {
+    fn default() -> Self {
+        Self::Strict
+    }
+}
+
+// What a call to `Board::flag_cell` did to the cell's flag.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FlagOutcome {
+    Added,
+    // The flag was replaced by a question mark.
+    Removed,
+    // No flag was added or removed: the cell was revealed or out of bounds, the game was over, or
+    // a question mark was cleared.
+    Ignored,
+    // The cell was left alone because the board already has as many flags as `max_flags` allows.
+    Capped,
+}
+
+// What a call to `Board::reveal_cell` did.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RevealOutcome {
+    // The cells that were uncovered, in breadth-first order from the one clicked.
+    Revealed(Vec<(usize, usize)>),
+    HitMine(usize, usize),
+    // Out of bounds, flagged or already revealed.
+    NoOp,
+    // The game had already been won or lost.
+    GameOver,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BoardError {
+    ZeroDimension,
+    // Every mine has to fit on the board with at least one safe cell left for the first reveal.
+    TooManyMines,
+    RaggedGrid,
+    MineOutOfBounds(usize, usize),
+    DuplicateMine(usize, usize),
+    // A saved board whose mine count disagrees with the mines in its grid.
+    MineCountMismatch,
+    // A saved board too large for its cells to be counted.
+    BadDimensions,
+}
+
+impl fmt::Display for BoardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ZeroDimension => write!(f, "board width and height must be non-zero"),
+            Self::TooManyMines => write!(f, "too many mines for the board size"),
+            Self::RaggedGrid => write!(f, "mine layout rows must all be the same length"),
+            Self::MineOutOfBounds(row, col) => {
+                write!(f, "mine at ({}, {}) is outside the board", row, col)
+            }
+            Self::DuplicateMine(row, col) => {
+                write!(f, "mine at ({}, {}) is listed twice", row, col)
+            }
+            Self::MineCountMismatch => write!(f, "mine count doesn't match the mines on the board"),
+            Self::BadDimensions => write!(f, "board dimensions are too large"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BoardError {}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(crate = "serde_crate"))]
+pub struct Board {
+    // Row-major, see `index`.
+    grid: Vec<CellState>,
+    width: usize,
+    height: usize,
+    state: GameState,
+    mines: usize,
+    mines_placed: bool,
+    seed: u64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    topology: Topology,
+    #[cfg_attr(feature = "serde", serde(default))]
+    connectivity: Connectivity,
+    #[cfg_attr(feature = "serde", serde(default))]
+    win_condition: WinCondition,
+    #[cfg_attr(feature = "serde", serde(default))]
+    flood_style: FloodStyle,
+    #[cfg_attr(feature = "serde", serde(default))]
+    chord_safety: ChordSafety,
+    // The most flags the player may place at once, if limited.
+    #[cfg_attr(feature = "serde", serde(default))]
+    max_flags: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    generation: GenerationOptions,
+    // The grid as it was before `reveal_all`, restored by `hide_all`.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    peek_snapshot: Option<Vec<CellState>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    undo: Option<Snapshot>,
+    // The moves applied so far, if recording, see `with_recording`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    recording: Option<Vec<Move>>,
+}
+
+// What `Board::undo` needs to roll back a single move.
+#[derive(Clone)]
+struct Snapshot {
+    grid: Vec<CellState>,
+    state: GameState,
+    mines_placed: bool,
+    // How many moves had been recorded.
+    moves: usize,
+}
+
+// Boards are equal when they have the same layout, cells and game state. The seed, options, undo
+// history and recorded moves aren't compared.
+impl PartialEq for Board {
+    fn eq(&self, other: &Self) -> bool {
+        self.width == other.width
+            && self.grid == other.grid
+            && self.state == other.state
+            && self.mines == other.mines
+    }
+}
+
+impl Eq for Board {}
+
+// The board with every cell shown, mines included, for debugging. `Board`'s own `Display` is the
+// player's view, where hidden cells are blank and flags are `!`. Supports `{:#}` the same way.
+pub struct FullView<'a>(&'a Board);
+
+impl fmt::Display for FullView<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut board = self.0.clone();
+        board.reveal_all();
+
+        if f.alternate() {
+            write!(f, "{:#}", board)
+        } else {
+            write!(f, "{}", board)
+        }
+    }
+}
+
+impl fmt::Debug for Board {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Board {}x{}, {} mines, {:?}\n{}",
+            self.width(),
+            self.height(),
+            self.mines,
+            self.state,
+            self
+        )
+    }
+}
+
+// `{:#}` adds row and column indices around the grid.
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let labels = f.alternate();
+        let label_width = if labels {
+            (self.height() - 1).to_string().len()
+        } else {
+            0
+        };
+        let margin = if labels {
+            " ".repeat(label_width + 1)
+        } else {
+            String::new()
+        };
+
+        if labels {
+            write!(f, "{}", margin)?;
+            for col in 0..self.width() {
+                write!(f, "  {:<2}", col)?;
+            }
+            writeln!(f)?;
+        }
+
+        write!(f, "{}", margin)?;
+        for _ in 0..self.width() {
+            write!(f, "----")?;
+        }
+        writeln!(f, "-")?;
+
+        for (row, col, cell) in self.iter_cells() {
+            if labels && col == 0 {
+                write!(f, "{:>1$} ", row, label_width)?;
+            }
+            write!(f, "| {} ", cell)?;
+            if col + 1 == self.width() {
+                write!(f, "|\n{}-", margin)?;
+                for _ in 0..self.width() {
+                    write!(f, "----")?;
+                }
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Picks `number` of the coords with a partial Fisher-Yates shuffle, moving them to the front. Done
+// by hand rather than with `SliceRandom` so that a seed gives the same board across rand versions.
+fn select_random_coords<'a, R: Rng + ?Sized>(
+    coords: &'a mut [(usize, usize)],
+    number: usize,
+    rng: &mut R,
+) -> &'a [(usize, usize)] {
+    let number = number.min(coords.len());
+
+    for i in 0..number {
+        let j = rng.gen_range(i, coords.len());
+        coords.swap(i, j);
+    }
+
+    &coords[..number]
+}
+
+impl Board {
+    #[cfg(feature = "std")]
+    pub fn new(width: usize, height: usize, mines: usize) -> Self {
+        Self::try_new(width, height, mines).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    #[cfg(feature = "std")]
+    pub fn try_new(width: usize, height: usize, mines: usize) -> Result<Self, BoardError> {
+        Self::try_new_with_rng(width, height, mines, &mut thread_rng())
+    }
+
+    // Like `try_new`, drawing the seed from `rng`, for targets without `thread_rng`.
+    pub fn try_new_with_rng<R: Rng + ?Sized>(
+        width: usize,
+        height: usize,
+        mines: usize,
+        rng: &mut R,
+    ) -> Result<Self, BoardError> {
+        Self::try_new_seeded(width, height, mines, rng.gen())
+    }
+
+    #[cfg(feature = "std")]
+    pub fn from_difficulty(difficulty: Difficulty) -> Self {
+        let (width, height, mines) = difficulty.parameters();
+        Self::new(width, height, mines)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn new_with_density(width: usize, height: usize, density: f32) -> Self {
+        assert!((0.0..=1.0).contains(&density));
+
+        let mines = ((width * height) as f32 * density).round() as usize;
+        Self::new(width, height, mines)
+    }
+
+    pub fn new_seeded(width: usize, height: usize, mines: usize, seed: u64) -> Self {
+        Self::try_new_seeded(width, height, mines, seed).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_new_seeded(
+        width: usize,
+        height: usize,
+        mines: usize,
+        seed: u64,
+    ) -> Result<Self, BoardError> {
+        if width == 0 || height == 0 {
+            return Err(BoardError::ZeroDimension);
+        }
+        if mines >= width * height {
+            return Err(BoardError::TooManyMines);
+        }
+
+        Ok(Self {
+            grid: vec![CellState::Neighbours(false, Marker::None, 0); width * height],
+            width,
+            height,
+            state: GameState::Playing,
+            mines,
+            mines_placed: false,
+            seed,
+            topology: Topology::Flat,
+            connectivity: Connectivity::Eight,
+            win_condition: WinCondition::AllSafeRevealed,
+            flood_style: FloodStyle::IncludeBorder,
+            chord_safety: ChordSafety::Strict,
+            max_flags: None,
+            generation: GenerationOptions::default(),
+            peek_snapshot: None,
+            undo: None,
+            recording: None,
+        })
+    }
+
+    // Builds a board with mines exactly where the layout says, e.g. for hand-authored puzzles.
+    pub fn from_grid(mines: &[&[bool]]) -> Result<Self, BoardError> {
+        let width = mines.first().map_or(0, |row| row.len());
+        if width == 0 {
+            return Err(BoardError::ZeroDimension);
+        }
+        if mines.iter().any(|row| row.len() != width) {
+            return Err(BoardError::RaggedGrid);
+        }
+
+        let grid = mines
+            .iter()
+            .flat_map(|row| row.iter())
+            .map(|&mine| {
+                if mine {
+                    CellState::Mine(false, Marker::None)
+                } else {
+                    CellState::Neighbours(false, Marker::None, 0)
+                }
+            })
+            .collect();
+
+        let mut board = Self {
+            grid,
+            width,
+            height: mines.len(),
+            state: GameState::Playing,
+            mines: mines
+                .iter()
+                .flat_map(|row| row.iter())
+                .filter(|&&mine| mine)
+                .count(),
+            mines_placed: true,
+            seed: 0,
+            topology: Topology::Flat,
+            connectivity: Connectivity::Eight,
+            win_condition: WinCondition::AllSafeRevealed,
+            flood_style: FloodStyle::IncludeBorder,
+            chord_safety: ChordSafety::Strict,
+            max_flags: None,
+            generation: GenerationOptions::default(),
+            peek_snapshot: None,
+            undo: None,
+            recording: None,
+        };
+        board.recount_neighbours();
+        Ok(board)
+    }
+
+    // Builds a board with mines at the given (row, col) coordinates.
+    pub fn with_mines(
+        width: usize,
+        height: usize,
+        mines: &[(usize, usize)],
+    ) -> Result<Self, BoardError> {
+        let mut layout = vec![vec![false; width]; height];
+        for &(row, col) in mines {
+            if !in_bounds(width, height, (row, col)) {
+                return Err(BoardError::MineOutOfBounds(row, col));
+            }
+            if layout[row][col] {
+                return Err(BoardError::DuplicateMine(row, col));
+            }
+            layout[row][col] = true;
+        }
+
+        let rows: Vec<&[bool]> = layout.iter().map(|row| row.as_slice()).collect();
+        Self::from_grid(&rows)
+    }
+
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        if topology == Topology::Torus {
+            assert!(
+                self.width() >= 3 && self.height() >= 3,
+                "torus boards must be at least 3x3"
+            );
+        }
+
+        self.topology = topology;
+        self.recount_neighbours();
+        self
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    pub fn with_connectivity(mut self, connectivity: Connectivity) -> Self {
+        self.connectivity = connectivity;
+        self.recount_neighbours();
+        self
+    }
+
+    pub fn connectivity(&self) -> Connectivity {
+        self.connectivity
+    }
+
+    pub fn with_win_condition(mut self, win_condition: WinCondition) -> Self {
+        self.win_condition = win_condition;
+        self.check_won();
+        self
+    }
+
+    pub fn win_condition(&self) -> WinCondition {
+        self.win_condition
+    }
+
+    pub fn with_flood_style(mut self, flood_style: FloodStyle) -> Self {
+        self.flood_style = flood_style;
+        self
+    }
+
+    pub fn flood_style(&self) -> FloodStyle {
+        self.flood_style
+    }
+
+    pub fn with_chord_safety(mut self, chord_safety: ChordSafety) -> Self {
+        self.chord_safety = chord_safety;
+        self
+    }
+
+    pub fn chord_safety(&self) -> ChordSafety {
+        self.chord_safety
+    }
+
+    pub fn with_max_flags(mut self, max_flags: Option<usize>) -> Self {
+        self.max_flags = max_flags;
+        self
+    }
+
+    pub fn max_flags(&self) -> Option<usize> {
+        self.max_flags
+    }
+
+    fn flags_left(&self) -> usize {
+        match self.max_flags {
+            Some(max) => max.saturating_sub(self.flags_placed()),
+            None => usize::MAX,
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    // Mines are placed on the first reveal so the first click (and its neighbours, if there's
+    // room) is never a mine.
+    fn place_mines(&mut self, safe_row: usize, safe_col: usize) {
+        let width = self.width();
+        let height = self.height();
+
+        let mut safe = self.first_click_safe_area(safe_row, safe_col);
+
+        if width * height - safe.len() < self.mines {
+            safe.truncate(1);
+        }
+
+        let mut coords = Vec::with_capacity(height * width);
+        for row in 0..height {
+            for col in 0..width {
+                if !safe.contains(&(row, col)) {
+                    coords.push((col, row));
+                }
+            }
+        }
+
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut attempt = 0;
+        let selected = loop {
+            let selected = select_random_coords(&mut coords, self.mines, &mut rng).to_vec();
+            if attempt == self.generation.retries || self.within_window_limit(&selected) {
+                break selected;
+            }
+            attempt += 1;
+        };
+
+        for (col, row) in selected {
+            let index = self.index(row, col);
+            let marker = match self.grid[index] {
+                CellState::Mine(_, marker) | CellState::Neighbours(_, marker, _) => marker,
+            };
+            self.grid[index] = CellState::Mine(false, marker);
+
+            for (o_row, o_col) in self.neighbours(row, col) {
+                let index = self.index(o_row, o_col);
+                if let CellState::Neighbours(_, _, ref mut value) = self.grid[index] {
+                    *value += 1;
+                }
+            }
+        }
+
+        self.mines_placed = true;
+    }
+
+    #[cfg(feature = "std")]
+    pub fn reset(&mut self) {
+        self.reset_seeded(thread_rng().gen());
+    }
+
+    // Starts a new game on the same board, keeping its size and options.
+    pub fn reset_seeded(&mut self, seed: u64) {
+        for cell in self.grid.iter_mut() {
+            *cell = CellState::Neighbours(false, Marker::None, 0);
+        }
+        self.state = GameState::Playing;
+        self.mines_placed = false;
+        self.seed = seed;
+        self.peek_snapshot = None;
+        self.undo = None;
+        if let Some(moves) = &mut self.recording {
+            moves.clear();
+        }
+    }
+
+    // Empties the board to design a layout on with `set_mine`. Nothing is left to place on the
+    // first reveal.
+    pub fn clear_mines(&mut self) {
+        self.reset_seeded(self.seed);
+        self.mines = 0;
+        self.mines_placed = true;
+    }
+
+    // Grows or shrinks the board, keeping the cells that still fit anchored to the top left. New
+    // cells are hidden and safe. Once mines are placed the mine count follows whichever were kept;
+    // before that it's kept, cut down if the smaller board can't hold that many.
+    pub fn resize(&mut self, new_width: usize, new_height: usize) {
+        assert!(
+            new_width > 0 && new_height > 0,
+            "{}",
+            BoardError::ZeroDimension
+        );
+        if self.topology == Topology::Torus {
+            assert!(
+                new_width >= 3 && new_height >= 3,
+                "torus boards must be at least 3x3"
+            );
+        }
+
+        let mut grid = vec![CellState::Neighbours(false, Marker::None, 0); new_width * new_height];
+        for (row, col, cell) in self.iter_cells() {
+            if row < new_height && col < new_width {
+                grid[row * new_width + col] = cell;
+            }
+        }
+
+        self.grid = grid;
+        self.width = new_width;
+        self.height = new_height;
+        if self.mines_placed {
+            self.mines = self.mine_positions().len();
+        } else {
+            self.mines = self.mines.min(new_width * new_height - 1);
+        }
+        self.peek_snapshot = None;
+        self.undo = None;
+        self.recount_neighbours();
+    }
+
+    fn recount_neighbours(&mut self) {
+        for row in 0..self.height() {
+            for col in 0..self.width() {
+                self.recompute_count(row, col);
+            }
+        }
+    }
+
+    // Adds or removes a mine, keeping the counts around it up to date. Meant for editing a layout:
+    // the cell ends up hidden, and the board no longer places mines on the first reveal.
+    pub fn set_mine(&mut self, row: usize, col: usize, is_mine: bool) {
+        if !in_bounds(self.width(), self.height(), (row, col)) {
+            return;
+        }
+        if !self.mines_placed {
+            // The mines waiting for the first reveal give way to the layout being drawn.
+            self.mines = 0;
+            self.mines_placed = true;
+        }
+
+        let index = self.index(row, col);
+        let (was_mine, marker) = match self.grid[index] {
+            CellState::Mine(_, marker) => (true, marker),
+            CellState::Neighbours(_, marker, _) => (false, marker),
+        };
+        if was_mine == is_mine {
+            return;
+        }
+
+        if is_mine {
+            self.grid[index] = CellState::Mine(false, marker);
+            self.mines += 1;
+        } else {
+            self.grid[index] = CellState::Neighbours(false, marker, 0);
+            self.mines -= 1;
+        }
+
+        self.recompute_count(row, col);
+        for (o_row, o_col) in self.neighbours(row, col) {
+            self.recompute_count(o_row, o_col);
+        }
+    }
+
+    // Recounts the mines around a single cell, e.g. after editing the layout next to it. Mines and
+    // out-of-bounds coordinates are left alone.
+    pub fn recompute_count(&mut self, row: usize, col: usize) {
+        if !in_bounds(self.width(), self.height(), (row, col)) {
+            return;
+        }
+
+        let index = self.index(row, col);
+        if let CellState::Neighbours(revealed, marker, _) = self.grid[index] {
+            let count = self
+                .neighbours(row, col)
+                .filter(|&(o_row, o_col)| {
+                    matches!(self.grid[self.index(o_row, o_col)], CellState::Mine(..))
+                })
+                .count();
+            self.grid[index] = CellState::Neighbours(revealed, marker, count as u8);
+        }
+    }
+
+    pub fn state(&self) -> GameState {
+        self.state
+    }
+
+    pub fn is_won(&self) -> bool {
+        self.state == GameState::Won
+    }
+
+    pub fn is_lost(&self) -> bool {
+        self.state == GameState::Lost
+    }
+
+    pub fn mine_count(&self) -> usize {
+        self.mines
+    }
+
+    // Whether the mines are down yet; until the first reveal every cell reads as a safe zero.
+    pub fn mines_placed(&self) -> bool {
+        self.mines_placed
+    }
+
+    pub fn flags_placed(&self) -> usize {
+        self.grid.iter().filter(|cell| is_flagged(cell)).count()
+    }
+
+    // The mines not yet accounted for by flags, negative once there are more flags than mines.
+    pub fn remaining_mines(&self) -> i64 {
+        self.mines as i64 - self.flags_placed() as i64
+    }
+
+    pub fn remaining_mines_clamped(&self) -> usize {
+        self.mines.saturating_sub(self.flags_placed())
+    }
+
+    // Flags on cells that aren't mines.
+    pub fn wrong_flag_count(&self) -> usize {
+        self.grid
+            .iter()
+            .filter(|cell| matches!(cell, CellState::Neighbours(false, Marker::Flag, _)))
+            .count()
+    }
+
+    // Mines that haven't been flagged yet.
+    pub fn missing_flag_count(&self) -> usize {
+        self.grid
+            .iter()
+            .filter(|cell| matches!(cell, CellState::Mine(..)) && !is_flagged(cell))
+            .count()
+    }
+
+    pub fn revealed_count(&self) -> usize {
+        self.grid
+            .iter()
+            .filter(|cell| matches!(cell, CellState::Neighbours(true, _, _)))
+            .count()
+    }
+
+    pub fn safe_cell_count(&self) -> usize {
+        self.width() * self.height() - self.mines
+    }
+
+    pub fn count_remaining_safe(&self) -> usize {
+        self.safe_cell_count() - self.revealed_count()
+    }
+
+    // How much of the board has been cleared, from 0 to 100. A board that's all mines has nothing
+    // left to clear, so counts as complete.
+    pub fn percent_complete(&self) -> f32 {
+        let safe = self.safe_cell_count();
+        if safe == 0 {
+            return 100.0;
+        }
+
+        self.revealed_count() as f32 / safe as f32 * 100.0
+    }
+
+    // Like `get_cell_state` for coordinates already known to be in bounds, e.g. when iterating
+    // over `0..height()` and `0..width()`.
+    pub fn cell(&self, row: usize, col: usize) -> CellState {
+        debug_assert!(in_bounds(self.width(), self.height(), (row, col)));
+        self.grid[self.index(row, col)]
+    }
+
+    // What's under a cell whether or not it's been revealed, for practice mode's hover. The same
+    // as `cell`, named for the callers that mean to look underneath.
+    pub fn peek_cell(&self, row: usize, col: usize) -> CellState {
+        self.cell(row, col)
+    }
+
+    pub fn get_cell_state(&self, row: usize, col: usize) -> Option<CellState> {
+        if !in_bounds(self.width(), self.height(), (row, col)) {
+            return None;
+        }
+
+        Some(self.grid[self.index(row, col)])
+    }
+
+    pub fn flag_cell(&mut self, row: usize, col: usize) -> FlagOutcome {
+        let before = self.snapshot();
+        self.record_move(Move::Flag(row, col));
+        let outcome = self.cycle_marker(row, col);
+        self.record_undo(before);
+        outcome
+    }
+
+    fn cycle_marker(&mut self, row: usize, col: usize) -> FlagOutcome {
+        // Like reveals, markers are frozen once the game is over.
+        if self.state != GameState::Playing {
+            return FlagOutcome::Ignored;
+        }
+
+        if !in_bounds(self.width(), self.height(), (row, col)) {
+            log::debug!("flag out of bounds: ({}, {})", row, col);
+            return FlagOutcome::Ignored;
+        }
+
+        let index = self.index(row, col);
+        let flags_left = self.flags_left();
+        let outcome = match &mut self.grid[index] {
+            CellState::Mine(false, Marker::None)
+            | CellState::Neighbours(false, Marker::None, _)
+                if flags_left == 0 =>
+            {
+                FlagOutcome::Capped
+            }
+            CellState::Mine(false, marker) | CellState::Neighbours(false, marker, _) => {
+                *marker = marker.next();
+                match marker {
+                    Marker::Flag => FlagOutcome::Added,
+                    Marker::Question => FlagOutcome::Removed,
+                    Marker::None => FlagOutcome::Ignored,
+                }
+            }
+            _ => FlagOutcome::Ignored,
+        };
+
+        self.check_won();
+        outcome
+    }
+
+    pub fn reveal_cell(&mut self, row: usize, col: usize) -> RevealOutcome {
+        let before = self.snapshot();
+        self.record_move(Move::Reveal(row, col));
+        let outcome = self.reveal(row, col);
+        self.record_undo(before);
+        outcome
+    }
+
+    fn reveal(&mut self, row: usize, col: usize) -> RevealOutcome {
+        if self.state != GameState::Playing {
+            return RevealOutcome::GameOver;
+        }
+
+        if !in_bounds(self.width(), self.height(), (row, col)) {
+            log::debug!("reveal out of bounds: ({}, {})", row, col);
+            return RevealOutcome::NoOp;
+        }
+
+        if !self.mines_placed {
+            self.place_mines(row, col);
+        }
+
+        let index = self.index(row, col);
+        let cell = &mut self.grid[index];
+
+        match cell {
+            CellState::Mine(false, Marker::Flag) => RevealOutcome::NoOp,
+            CellState::Neighbours(false, Marker::Flag, _) => RevealOutcome::NoOp,
+            CellState::Mine(ref mut revealed, _) if !*revealed => {
+                *revealed = true;
+                log::debug!("hit a mine at ({}, {})", row, col);
+                self.state = GameState::Lost;
+                self.reveal_mines();
+                RevealOutcome::HitMine(row, col)
+            }
+            CellState::Mine(true, _) | CellState::Neighbours(true, _, _) => RevealOutcome::NoOp,
+            _ => {
+                let revealed = self.reveal_cell_dfs(row, col);
+                log::trace!("revealed {} cells from ({}, {})", revealed.len(), row, col);
+                self.check_won();
+                RevealOutcome::Revealed(revealed)
+            }
+        }
+    }
+
+    // Reveals like `reveal_cell`, returning the cells the flood uncovered grouped by their
+    // Manhattan distance from (row, col), nearest first. Cells the same distance away keep their
+    // breadth-first order.
+    pub fn reveal_cell_ordered(&mut self, row: usize, col: usize) -> Vec<(usize, usize)> {
+        match self.reveal_cell(row, col) {
+            RevealOutcome::Revealed(mut cells) => {
+                cells.sort_by_key(|&cell| manhattan_distance((row, col), cell));
+                cells
+            }
+            RevealOutcome::HitMine(row, col) => vec![(row, col)],
+            RevealOutcome::NoOp | RevealOutcome::GameOver => Vec::new(),
+        }
+    }
+
+    fn reveal_mines(&mut self) {
+        for cell in self.grid.iter_mut() {
+            if let CellState::Mine(ref mut revealed, _) = cell {
+                *revealed = true;
+            }
+        }
+    }
+
+    fn check_won(&mut self) {
+        // Everything shows as revealed while peeking, which mustn't count as clearing the board.
+        if self.state != GameState::Playing || !self.mines_placed || self.is_peeking() {
+            return;
+        }
+
+        let won = match self.win_condition {
+            WinCondition::AllSafeRevealed => self.count_remaining_safe() == 0,
+            WinCondition::AllMinesFlagged => self
+                .grid
+                .iter()
+                .all(|cell| matches!(cell, CellState::Mine(..)) == is_flagged(cell)),
+        };
+
+        if won {
+            log::debug!("game won");
+            self.state = GameState::Won;
+
+            // The mines left hidden are all known now, so flag them for the player.
+            for cell in self.grid.iter_mut() {
+                if let CellState::Mine(false, marker) = cell {
+                    *marker = Marker::Flag;
+                }
+            }
+        }
+    }
+
+    pub fn chord_cell(&mut self, row: usize, col: usize) {
+        let before = self.snapshot();
+        self.record_move(Move::Chord(row, col));
+        self.chord(row, col);
+        self.record_undo(before);
+    }
+
+    fn chord(&mut self, row: usize, col: usize) {
+        let count = match self.get_cell_state(row, col) {
+            Some(CellState::Neighbours(true, _, count)) => count,
+            _ => return,
+        };
+
+        if self.adjacent_flag_count(row, col) != count {
+            return;
+        }
+
+        if self.chord_safety == ChordSafety::Safe {
+            let misflagged = self.neighbours(row, col).any(|(o_row, o_col)| {
+                matches!(
+                    self.grid[self.index(o_row, o_col)],
+                    CellState::Neighbours(false, Marker::Flag, _)
+                )
+            });
+            if misflagged {
+                return;
+            }
+        }
+
+        for (o_row, o_col) in self.neighbours(row, col) {
+            match self.grid[self.index(o_row, o_col)] {
+                CellState::Neighbours(true, _, _) => {}
+                cell if is_flagged(&cell) => {}
+                _ => {
+                    self.reveal(o_row, o_col);
+                }
+            }
+        }
+    }
+
+    // Flags every hidden neighbour of a revealed number when they can only all be mines.
+    pub fn auto_flag(&mut self, row: usize, col: usize) {
+        let before = self.snapshot();
+        self.flag_forced(row, col);
+        self.record_undo(before);
+    }
+
+    fn flag_forced(&mut self, row: usize, col: usize) {
+        // A peek reveals every cell, so the counts below say nothing about the real board.
+        if self.state != GameState::Playing || self.is_peeking() {
+            return;
+        }
+
+        let count = match self.get_cell_state(row, col) {
+            Some(CellState::Neighbours(true, _, count)) => count,
+            _ => return,
+        };
+
+        if self.adjacent_unrevealed_count(row, col) != count {
+            return;
+        }
+
+        let unflagged = match count.checked_sub(self.adjacent_flag_count(row, col)) {
+            Some(unflagged) => unflagged,
+            None => return,
+        };
+        if unflagged as usize > self.flags_left() {
+            return;
+        }
+
+        let hidden: Vec<(usize, usize)> = self
+            .neighbours(row, col)
+            .filter(|&(o_row, o_col)| !is_revealed(&self.grid[self.index(o_row, o_col)]))
+            .collect();
+        for (o_row, o_col) in hidden {
+            self.set_marker(o_row, o_col, Marker::Flag);
+        }
+    }
+
+    // Rolls back the last reveal, flag, chord or solver step, including a losing reveal. Only one
+    // move is remembered; returns whether there was anything to undo.
+    pub fn undo(&mut self) -> bool {
+        match self.undo.take() {
+            Some(snapshot) => {
+                self.grid = snapshot.grid;
+                self.state = snapshot.state;
+                self.mines_placed = snapshot.mines_placed;
+                if let Some(moves) = &mut self.recording {
+                    moves.truncate(snapshot.moves);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            grid: self.grid.clone(),
+            state: self.state,
+            mines_placed: self.mines_placed,
+            moves: self.recorded_moves().len(),
+        }
+    }
+
+    // Keeps `before` for `undo`, unless the move didn't change anything.
+    fn record_undo(&mut self, before: Snapshot) {
+        if before.grid != self.grid || before.state != self.state {
+            self.undo = Some(before);
+        }
+    }
+
+    // Shows every cell without affecting the game state, until `hide_all` is called.
+    pub fn reveal_all(&mut self) {
+        if self.peek_snapshot.is_none() {
+            self.peek_snapshot = Some(self.grid.clone());
+        }
+
+        for cell in self.grid.iter_mut() {
+            match cell {
+                CellState::Mine(revealed, _) | CellState::Neighbours(revealed, _, _) => {
+                    *revealed = true;
+                }
+            }
+        }
+    }
+
+    pub fn hide_all(&mut self) {
+        if let Some(grid) = self.peek_snapshot.take() {
+            self.grid = grid;
+        }
+    }
+
+    pub fn is_peeking(&self) -> bool {
+        self.peek_snapshot.is_some()
+    }
+
+    pub fn full_view(&self) -> FullView<'_> {
+        FullView(self)
+    }
+
+    // Every cell with its coordinates, in row-major order.
+    pub fn iter_cells(&self) -> impl Iterator<Item = (usize, usize, CellState)> + '_ {
+        let width = self.width;
+        self.grid
+            .iter()
+            .enumerate()
+            .map(move |(index, &cell)| (index / width, index % width, cell))
+    }
+
+    // Where the mines are, in row-major order. Empty until the first reveal places them.
+    pub fn mine_positions(&self) -> Vec<(usize, usize)> {
+        self.iter_cells()
+            .filter(|(_, _, cell)| matches!(cell, CellState::Mine(..)))
+            .map(|(row, col, _)| (row, col))
+            .collect()
+    }
+
+    // How many of the cells around (row, col) are flagged.
+    pub fn adjacent_flag_count(&self, row: usize, col: usize) -> u8 {
+        self.neighbours(row, col)
+            .filter(|&(o_row, o_col)| is_flagged(&self.grid[self.index(o_row, o_col)]))
+            .count() as u8
+    }
+
+    // Whether (row, col) is a revealed number with exactly as many flags around it as mines.
+    pub fn is_number_satisfied(&self, row: usize, col: usize) -> bool {
+        match self.get_cell_state(row, col) {
+            Some(CellState::Neighbours(true, _, count)) => {
+                self.adjacent_flag_count(row, col) == count
+            }
+            _ => false,
+        }
+    }
+
+    // How many of the cells around (row, col) are still hidden, flagged or not.
+    pub fn adjacent_unrevealed_count(&self, row: usize, col: usize) -> u8 {
+        self.neighbours(row, col)
+            .filter(|&(o_row, o_col)| !is_revealed(&self.grid[self.index(o_row, o_col)]))
+            .count() as u8
+    }
+
+    // The in-bounds coordinates around a cell. The iterator doesn't borrow the board, so it can be
+    // used while mutating cells.
+    pub fn neighbours(&self, row: usize, col: usize) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width();
+        let height = self.height();
+        let topology = self.topology;
+        let directions = match self.connectivity {
+            Connectivity::Eight => &ALL_DIRECTIONS[..],
+            Connectivity::Four => &ALL_DIRECTIONS[..4],
+        };
+
+        directions
+            .iter()
+            .filter_map(move |direction| match topology {
+                Topology::Flat => direction
+                    .offset((row, col))
+                    .filter(|&offset| in_bounds(width, height, offset)),
+                Topology::Torus => Some(direction.wrapping_offset((row, col), width, height)),
+            })
+    }
+
+    fn set_marker(&mut self, row: usize, col: usize, new_marker: Marker) {
+        let index = self.index(row, col);
+        if let CellState::Mine(false, marker) | CellState::Neighbours(false, marker, _) =
+            self.grid[index]
+        {
+            self.record_marker(row, col, marker, new_marker);
+        }
+
+        match &mut self.grid[index] {
+            CellState::Mine(false, marker) | CellState::Neighbours(false, marker, _) => {
+                *marker = new_marker;
+            }
+            _ => {}
+        }
+
+        self.check_won();
+    }
+
+    // Reveals an unrevealed cell and, if it has no neighbouring mines, floods outwards. Only
+    // unrevealed zero cells are expanded; numbered cells on the border are revealed in place unless
+    // the flood style is `ZeroOnly`. Flagged cells are walls the flood doesn't cross. Uses an
+    // explicit queue so huge open boards can't overflow the call stack, and returns the cells it
+    // revealed in breadth-first order.
+    fn reveal_cell_dfs(&mut self, row: usize, col: usize) -> Vec<(usize, usize)> {
+        let index = self.index(row, col);
+        match &mut self.grid[index] {
+            CellState::Neighbours(revealed, marker, count) => {
+                *revealed = true;
+                *marker = Marker::None;
+                if *count > 0 {
+                    return vec![(row, col)];
+                }
+            }
+            CellState::Mine(..) => return Vec::new(),
+        }
+
+        let mut revealed = vec![(row, col)];
+        let mut queue = VecDeque::new();
+        queue.push_back((row, col));
+
+        while let Some((row, col)) = queue.pop_front() {
+            for (o_row, o_col) in self.neighbours(row, col) {
+                let index = self.index(o_row, o_col);
+                match self.grid[index] {
+                    CellState::Neighbours(false, Marker::Flag, _) => {}
+                    CellState::Neighbours(false, _, count)
+                        if count == 0 || self.flood_style == FloodStyle::IncludeBorder =>
+                    {
+                        self.grid[index] = CellState::Neighbours(true, Marker::None, count);
+                        revealed.push((o_row, o_col));
+                        if count == 0 {
+                            queue.push_back((o_row, o_col));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        revealed
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    fn index(&self, row: usize, col: usize) -> usize {
+        row * self.width + col
+    }
+}
+
+pub fn in_bounds(width: usize, height: usize, (row, col): (usize, usize)) -> bool {
+    row < height && col < width
+}
+
+fn manhattan_distance(from: (usize, usize), to: (usize, usize)) -> usize {
+    let rows = (from.0 as isize - to.0 as isize).abs();
+    let cols = (from.1 as isize - to.1 as isize).abs();
+    (rows + cols) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Builds a board from rows like "*..", where `*` is a mine.
+    pub(crate) fn board(rows: &[&str]) -> Board {
+        let layout: Vec<Vec<bool>> = rows
+            .iter()
+            .map(|row| row.chars().map(|c| c == '*').collect())
+            .collect();
+        let rows: Vec<&[bool]> = layout.iter().map(|row| row.as_slice()).collect();
+        Board::from_grid(&rows).unwrap()
+    }
+
+    // A file in the temp directory that's removed again once the test is done with it.
+    #[cfg(feature = "std")]
+    pub(crate) struct TempFile(pub(crate) std::path::PathBuf);
+
+    #[cfg(feature = "std")]
+    impl TempFile {
+        pub(crate) fn new(name: &str) -> Self {
+            Self(std::env::temp_dir().join(format!(
+                "minesweepe-rs-{}-{}.json",
+                name,
+                std::process::id()
+            )))
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn get_cell_state_covers_non_square_board() {
+        let board = Board::new_seeded(5, 3, 2, 1);
+
+        for row in 0..board.height() {
+            for col in 0..board.width() {
+                assert!(board.get_cell_state(row, col).is_some());
+            }
+        }
+        assert_eq!(board.get_cell_state(3, 0), None);
+        assert_eq!(board.get_cell_state(0, 5), None);
+    }
+
+    #[test]
+    fn revealing_every_safe_cell_wins() {
+        let mut board = board(&["*.", ".."]);
+
+        board.reveal_cell(0, 1);
+        board.reveal_cell(1, 0);
+        assert_eq!(board.state(), GameState::Playing);
+        board.reveal_cell(1, 1);
+        assert_eq!(board.state(), GameState::Won);
+    }
+
+    #[test]
+    fn first_reveal_is_never_a_mine() {
+        for seed in 0..200 {
+            let mut board = Board::new_seeded(9, 9, 70, seed);
+            let outcome = board.reveal_cell(4, 4);
+
+            assert!(
+                matches!(outcome, RevealOutcome::Revealed(_)),
+                "seed {}",
+                seed
+            );
+            assert!(matches!(board.cell(4, 4), CellState::Neighbours(true, ..)));
+        }
+    }
+
+    #[test]
+    fn same_seed_places_same_mines() {
+        let mut first = Board::new_seeded(16, 16, 40, 42);
+        let mut second = Board::new_seeded(16, 16, 40, 42);
+        first.reveal_cell(3, 7);
+        second.reveal_cell(3, 7);
+
+        assert_eq!(first.mine_positions().len(), 40);
+        assert_eq!(first.mine_positions(), second.mine_positions());
+    }
+
+    #[test]
+    fn counts_mines_and_flags() {
+        let mut board = board(&["*..", "..*", "..."]);
+        assert_eq!(board.mine_count(), 2);
+        assert_eq!(board.flags_placed(), 0);
+
+        board.flag_cell(0, 0);
+        board.flag_cell(2, 2);
+        assert_eq!(board.flags_placed(), 2);
+        assert_eq!(board.remaining_mines(), 0);
+    }
+
+    #[test]
+    fn chord_with_correct_flags_reveals_neighbours() {
+        let mut board = board(&["*..", "...", "..."]);
+        board.reveal_cell(1, 1);
+        board.flag_cell(0, 0);
+
+        board.chord_cell(1, 1);
+        assert!(matches!(board.cell(0, 1), CellState::Neighbours(true, ..)));
+        assert!(matches!(board.cell(2, 2), CellState::Neighbours(true, ..)));
+        assert_eq!(board.cell(0, 0), CellState::Mine(false, Marker::Flag));
+        assert!(board.is_won());
+    }
+
+    #[test]
+    fn chord_with_wrong_flags_sets_off_the_mine() {
+        let mut board = board(&["*..", "...", "..."]);
+        board.reveal_cell(1, 1);
+        board.flag_cell(0, 1);
+
+        board.chord_cell(1, 1);
+        assert_eq!(board.cell(0, 0), CellState::Mine(true, Marker::None));
+        assert!(board.is_lost());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn difficulty_presets() {
+        assert_eq!(Difficulty::Beginner.parameters(), (9, 9, 10));
+        assert_eq!(Difficulty::Intermediate.parameters(), (16, 16, 40));
+        assert_eq!(Difficulty::Expert.parameters(), (30, 16, 99));
+
+        let board = Board::from_difficulty(Difficulty::Expert);
+        assert_eq!((board.width(), board.height()), (30, 16));
+        assert_eq!(board.mine_count(), 99);
+        assert_eq!("Intermediate".parse(), Ok(Difficulty::Intermediate));
+    }
+
+    #[test]
+    fn losing_reveals_every_mine() {
+        let mut board = board(&["*..", "..*", "*.."]);
+        board.flag_cell(1, 2);
+
+        assert_eq!(board.reveal_cell(0, 0), RevealOutcome::HitMine(0, 0));
+        for (row, col) in board.mine_positions() {
+            assert!(matches!(board.cell(row, col), CellState::Mine(true, _)));
+        }
+        assert_eq!(board.cell(1, 2), CellState::Mine(true, Marker::Flag));
+    }
+
+    #[test]
+    fn markers_cycle_on_any_hidden_cell() {
+        let mut board = board(&["*.", ".."]);
+
+        for &(row, col) in &[(0, 0), (0, 1)] {
+            assert_eq!(board.flag_cell(row, col), FlagOutcome::Added);
+            assert!(is_flagged(&board.cell(row, col)));
+            assert_eq!(board.flag_cell(row, col), FlagOutcome::Removed);
+            assert!(matches!(
+                board.cell(row, col),
+                CellState::Mine(false, Marker::Question)
+                    | CellState::Neighbours(false, Marker::Question, _)
+            ));
+            assert_eq!(board.flag_cell(row, col), FlagOutcome::Ignored);
+            assert!(matches!(
+                board.cell(row, col),
+                CellState::Mine(false, Marker::None)
+                    | CellState::Neighbours(false, Marker::None, _)
+            ));
+        }
+    }
+
+    #[test]
+    fn direction_offsets() {
+        let offsets = |cell| {
+            ALL_DIRECTIONS
+                .iter()
+                .map(|direction| direction.offset(cell))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            offsets((5, 5)),
+            vec![
+                Some((4, 5)),
+                Some((5, 6)),
+                Some((6, 5)),
+                Some((5, 4)),
+                Some((4, 6)),
+                Some((6, 6)),
+                Some((6, 4)),
+                Some((4, 4)),
+            ]
+        );
+        assert_eq!(
+            offsets((0, 0)),
+            vec![
+                None,
+                Some((0, 1)),
+                Some((1, 0)),
+                None,
+                None,
+                Some((1, 1)),
+                None,
+                None
+            ]
+        );
+        assert_eq!(
+            offsets((0, 9)),
+            vec![
+                None,
+                Some((0, 10)),
+                Some((1, 9)),
+                Some((0, 8)),
+                None,
+                Some((1, 10)),
+                Some((1, 8)),
+                None
+            ]
+        );
+        assert_eq!(
+            offsets((9, 0)),
+            vec![
+                Some((8, 0)),
+                Some((9, 1)),
+                Some((10, 0)),
+                None,
+                Some((8, 1)),
+                Some((10, 1)),
+                None,
+                None
+            ]
+        );
+        assert_eq!(
+            offsets((9, 9)),
+            vec![
+                Some((8, 9)),
+                Some((9, 10)),
+                Some((10, 9)),
+                Some((9, 8)),
+                Some((8, 10)),
+                Some((10, 10)),
+                Some((10, 8)),
+                Some((8, 8)),
+            ]
+        );
+
+        // Only the offsets that stay on a 10x10 board survive `in_bounds`.
+        let on_board = |cell| {
+            offsets(cell)
+                .into_iter()
+                .flatten()
+                .filter(|&offset| in_bounds(10, 10, offset))
+                .count()
+        };
+        assert_eq!(on_board((0, 0)), 3);
+        assert_eq!(on_board((0, 9)), 3);
+        assert_eq!(on_board((9, 0)), 3);
+        assert_eq!(on_board((9, 9)), 3);
+        assert_eq!(on_board((5, 5)), 8);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn density_sets_the_mine_count() {
+        assert_eq!(Board::new_with_density(10, 10, 0.0).mine_count(), 0);
+        assert_eq!(Board::new_with_density(10, 10, 0.15).mine_count(), 15);
+        assert_eq!(Board::new_with_density(8, 5, 0.5).mine_count(), 20);
+
+        let mut empty = Board::new_with_density(4, 4, 0.0);
+        empty.reveal_cell(0, 0);
+        assert!(empty.mine_positions().is_empty());
+        assert!(empty.is_won());
+    }
+
+    #[test]
+    fn flood_stops_at_the_numbered_border() {
+        let mut board = board(&["..*..", "..*..", "..*.."]);
+
+        let mut revealed = match board.reveal_cell(0, 0) {
+            RevealOutcome::Revealed(cells) => cells,
+            outcome => panic!("unexpected {:?}", outcome),
+        };
+        revealed.sort();
+        assert_eq!(
+            revealed,
+            vec![(0, 0), (0, 1), (1, 0), (1, 1), (2, 0), (2, 1)]
+        );
+        assert_eq!(board.revealed_count(), 6);
+    }
+
+    #[test]
+    fn neighbour_counts_at_corner_edge_and_centre() {
+        let board = Board::new_seeded(5, 4, 1, 0);
+
+        assert_eq!(board.neighbours(0, 0).count(), 3);
+        assert_eq!(board.neighbours(3, 4).count(), 3);
+        assert_eq!(board.neighbours(0, 2).count(), 5);
+        assert_eq!(board.neighbours(2, 0).count(), 5);
+        assert_eq!(board.neighbours(1, 2).count(), 8);
+    }
+
+    #[test]
+    fn cell_agrees_with_get_cell_state() {
+        let mut board = Board::new_seeded(7, 5, 8, 3);
+        board.reveal_cell(2, 3);
+        board.flag_cell(0, 0);
+
+        for row in 0..board.height() {
+            for col in 0..board.width() {
+                assert_eq!(
+                    board.cell(row, col),
+                    board.get_cell_state(row, col).unwrap()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn torus_neighbours_wrap_around() {
+        let board = board(&["....", "....", "....", "...*"]).with_topology(Topology::Torus);
+
+        let mut neighbours: Vec<_> = board.neighbours(0, 0).collect();
+        neighbours.sort();
+        assert_eq!(
+            neighbours,
+            vec![
+                (0, 1),
+                (0, 3),
+                (1, 0),
+                (1, 1),
+                (1, 3),
+                (3, 0),
+                (3, 1),
+                (3, 3)
+            ]
+        );
+        assert_eq!(
+            board.cell(0, 0),
+            CellState::Neighbours(false, Marker::None, 1)
+        );
+        assert_eq!(
+            board.cell(3, 0),
+            CellState::Neighbours(false, Marker::None, 1)
+        );
+        assert_eq!(
+            board.cell(1, 1),
+            CellState::Neighbours(false, Marker::None, 0)
+        );
+    }
+
+    #[test]
+    fn won_and_lost_states() {
+        let fresh = board(&["*.", ".."]);
+        assert!(!fresh.is_won() && !fresh.is_lost());
+
+        let mut lost = fresh.clone();
+        lost.reveal_cell(0, 0);
+        assert!(lost.is_lost() && !lost.is_won());
+
+        let mut won = fresh;
+        won.reveal_cell(0, 1);
+        won.reveal_cell(1, 0);
+        won.reveal_cell(1, 1);
+        assert!(won.is_won() && !won.is_lost());
+    }
+
+    #[test]
+    fn ordered_reveal_spreads_by_manhattan_distance() {
+        let mut board = board(&["......", "......", "......", "......", ".....*"]);
+
+        let cells = board.reveal_cell_ordered(1, 2);
+        assert_eq!(cells.len(), board.revealed_count());
+        assert_eq!(cells[0], (1, 2));
+        let distances: Vec<usize> = cells
+            .iter()
+            .map(|&cell| manhattan_distance((1, 2), cell))
+            .collect();
+        assert!(distances.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(*distances.last().unwrap(), 5);
+    }
+
+    #[test]
+    fn revealed_count_matches_the_opened_region() {
+        let mut board = board(&["...*..", "...*..", "...*.."]);
+        assert_eq!(board.revealed_count(), 0);
+
+        board.reveal_cell(1, 0);
+        assert_eq!(board.revealed_count(), 9);
+        board.flag_cell(0, 5);
+        board.reveal_cell(2, 4);
+        assert_eq!(board.revealed_count(), 10);
+    }
+
+    #[test]
+    fn auto_flag_flags_only_forced_mines() {
+        let mut board = board(&["*..*."]);
+        board.reveal_cell(0, 1);
+
+        // (0, 0) and (0, 2) could each be the mine.
+        board.auto_flag(0, 1);
+        assert_eq!(board.flags_placed(), 0);
+        board.auto_flag(0, 4);
+        assert_eq!(board.flags_placed(), 0);
+
+        board.reveal_cell(0, 2);
+        board.auto_flag(0, 1);
+        assert_eq!(board.cell(0, 0), CellState::Mine(false, Marker::Flag));
+        assert_eq!(board.flags_placed(), 1);
+    }
+
+    #[test]
+    fn zero_dimension_is_an_error() {
+        assert_eq!(
+            Board::try_new_seeded(0, 5, 0, 0),
+            Err(BoardError::ZeroDimension)
+        );
+        assert_eq!(
+            Board::try_new_seeded(5, 0, 0, 0),
+            Err(BoardError::ZeroDimension)
+        );
+    }
+
+    #[test]
+    fn too_many_mines_is_an_error() {
+        assert_eq!(
+            Board::try_new_seeded(3, 3, 9, 0),
+            Err(BoardError::TooManyMines)
+        );
+        assert!(Board::try_new_seeded(3, 3, 8, 0).is_ok());
+    }
+
+    #[test]
+    fn ragged_grid_is_an_error() {
+        assert_eq!(
+            Board::from_grid(&[&[false, true], &[false]]),
+            Err(BoardError::RaggedGrid)
+        );
+    }
+
+    #[test]
+    fn mine_out_of_bounds_is_an_error() {
+        assert_eq!(
+            Board::with_mines(3, 2, &[(2, 0)]),
+            Err(BoardError::MineOutOfBounds(2, 0))
+        );
+    }
+
+    #[test]
+    fn duplicate_mine_is_an_error() {
+        assert_eq!(
+            Board::with_mines(3, 2, &[(1, 1), (0, 2), (1, 1)]),
+            Err(BoardError::DuplicateMine(1, 1))
+        );
+    }
+
+    #[test]
+    fn from_grid_counts_neighbours() {
+        let board = Board::from_grid(&[
+            &[true, false, false],
+            &[false, false, true],
+            &[false, false, false],
+        ])
+        .unwrap();
+
+        let counts: Vec<Option<u8>> = board
+            .iter_cells()
+            .map(|(_, _, cell)| match cell {
+                CellState::Neighbours(false, Marker::None, count) => Some(count),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            counts,
+            vec![
+                None,
+                Some(2),
+                Some(1),
+                Some(1),
+                Some(2),
+                None,
+                Some(0),
+                Some(1),
+                Some(1),
+            ]
+        );
+        assert_eq!(board.mine_count(), 2);
+        assert!(board.mines_placed());
+    }
+
+    #[test]
+    fn from_grid_rejects_ragged_and_empty_layouts() {
+        assert_eq!(
+            Board::from_grid(&[&[false], &[false, true], &[false]]),
+            Err(BoardError::RaggedGrid)
+        );
+        assert_eq!(Board::from_grid(&[]), Err(BoardError::ZeroDimension));
+        assert_eq!(Board::from_grid(&[&[]]), Err(BoardError::ZeroDimension));
+    }
+
+    #[test]
+    fn flags_wall_off_the_flood() {
+        let mut board = board(&["....*"]);
+        board.flag_cell(0, 2);
+
+        assert_eq!(
+            board.reveal_cell(0, 0),
+            RevealOutcome::Revealed(vec![(0, 0), (0, 1)])
+        );
+        assert_eq!(
+            board.cell(0, 2),
+            CellState::Neighbours(false, Marker::Flag, 0)
+        );
+        assert_eq!(
+            board.cell(0, 3),
+            CellState::Neighbours(false, Marker::None, 1)
+        );
+    }
+
+    #[test]
+    fn win_conditions() {
+        let layout = board(&["*..", "...", "..."]);
+
+        let mut revealing = layout.clone();
+        revealing.reveal_cell(2, 2);
+        assert!(revealing.is_won());
+
+        let mut flagging = layout.with_win_condition(WinCondition::AllMinesFlagged);
+        flagging.reveal_cell(2, 2);
+        assert_eq!(flagging.count_remaining_safe(), 0);
+        assert!(!flagging.is_won());
+        flagging.flag_cell(0, 0);
+        assert!(flagging.is_won());
+    }
+
+    #[test]
+    fn iter_cells_is_row_major() {
+        let board = board(&["*..", "..*"]);
+
+        let cells: Vec<(usize, usize, CellState)> = board.iter_cells().collect();
+        assert_eq!(cells.len(), 6);
+        let coords: Vec<(usize, usize)> = cells.iter().map(|&(row, col, _)| (row, col)).collect();
+        assert_eq!(coords, vec![(0, 0), (0, 1), (0, 2), (1, 0), (1, 1), (1, 2)]);
+        for (row, col, cell) in cells {
+            assert_eq!(cell, board.cell(row, col));
+        }
+    }
+
+    // The flood as a grid of rows would compute it, for checking the flat grid against.
+    fn nested_flood(mines: &[Vec<bool>], start: (usize, usize)) -> Vec<(usize, usize)> {
+        let (height, width) = (mines.len(), mines[0].len());
+        let around = |row: usize, col: usize| {
+            let rows = row.saturating_sub(1)..=(row + 1).min(height - 1);
+            rows.flat_map(move |o_row| {
+                let cols = col.saturating_sub(1)..=(col + 1).min(width - 1);
+                cols.map(move |o_col| (o_row, o_col))
+            })
+            .filter(move |&cell| cell != (row, col))
+        };
+
+        let mut revealed = vec![vec![false; width]; height];
+        let mut stack = vec![start];
+        revealed[start.0][start.1] = true;
+        while let Some((row, col)) = stack.pop() {
+            if around(row, col).any(|(o_row, o_col)| mines[o_row][o_col]) {
+                continue;
+            }
+            for (o_row, o_col) in around(row, col) {
+                if !revealed[o_row][o_col] {
+                    revealed[o_row][o_col] = true;
+                    stack.push((o_row, o_col));
+                }
+            }
+        }
+
+        let mut cells = Vec::new();
+        for (row, cols) in revealed.iter().enumerate() {
+            for (col, &shown) in cols.iter().enumerate() {
+                if shown {
+                    cells.push((row, col));
+                }
+            }
+        }
+        cells
+    }
+
+    #[test]
+    fn flat_grid_reveals_what_nested_rows_would() {
+        for seed in 0..5 {
+            let mut board = Board::new_seeded(40, 30, 150, seed);
+            board.reveal_cell(15, 20);
+
+            let mut mines = vec![vec![false; board.width()]; board.height()];
+            for (row, col) in board.mine_positions() {
+                mines[row][col] = true;
+            }
+            let revealed: Vec<(usize, usize)> = board
+                .iter_cells()
+                .filter(|(_, _, cell)| is_revealed(cell))
+                .map(|(row, col, _)| (row, col))
+                .collect();
+            assert_eq!(revealed, nested_flood(&mines, (15, 20)), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn reveals_on_a_500x500_board() {
+        let mut board = Board::new_seeded(500, 500, 25_000, 9);
+
+        let revealed = match board.reveal_cell(250, 250) {
+            RevealOutcome::Revealed(cells) => cells.len(),
+            outcome => panic!("unexpected {:?}", outcome),
+        };
+        assert_eq!(board.revealed_count(), revealed);
+        assert_eq!(board.iter_cells().count(), 250_000);
+        assert_eq!(board.mine_positions().len(), 25_000);
+    }
+
+    #[test]
+    fn floods_a_huge_open_board() {
+        let mut board = Board::new_seeded(1000, 1000, 0, 0);
+
+        board.reveal_cell(0, 0);
+        assert_eq!(board.revealed_count(), 1_000_000);
+        assert!(board.is_won());
+    }
+
+    #[test]
+    fn peeking_reveals_everything_without_ending_the_game() {
+        let mut board = board(&["*..", "...", "..*"]);
+        board.reveal_cell(0, 2);
+        let before = board.clone();
+
+        board.reveal_all();
+        for (row, col, cell) in board.iter_cells() {
+            assert!(is_revealed(&cell), "({}, {}) is hidden", row, col);
+        }
+        board.flag_cell(1, 1);
+        board.auto_flag(0, 1);
+        assert_eq!(board.state(), GameState::Playing);
+        let mut board = board.with_win_condition(WinCondition::AllSafeRevealed);
+        assert_eq!(board.state(), GameState::Playing);
+
+        board.hide_all();
+        assert_eq!(board, before);
+    }
+
+    #[test]
+    fn undo_restores_the_grid_before_a_reveal() {
+        let mut board = board(&["*...", "....", "...*"]);
+        board.reveal_cell(1, 1);
+        let before = board.clone();
+
+        board.reveal_cell(0, 3);
+        assert_ne!(board, before);
+        assert!(board.undo());
+        assert_eq!(board, before);
+        assert!(!board.undo());
+
+        board.reveal_cell(0, 0);
+        assert!(board.is_lost());
+        assert!(board.undo());
+        assert_eq!(board, before);
+    }
+
+    #[test]
+    fn four_connectivity_ignores_diagonal_mines() {
+        let eight = board(&["*.*", "...", "*.."]);
+        let four = eight.clone().with_connectivity(Connectivity::Four);
+
+        assert_eq!(
+            eight.cell(1, 1),
+            CellState::Neighbours(false, Marker::None, 3)
+        );
+        assert_eq!(
+            four.cell(1, 1),
+            CellState::Neighbours(false, Marker::None, 0)
+        );
+        assert_eq!(
+            eight.cell(0, 1),
+            CellState::Neighbours(false, Marker::None, 2)
+        );
+        assert_eq!(
+            four.cell(0, 1),
+            CellState::Neighbours(false, Marker::None, 2)
+        );
+        assert_eq!(four.neighbours(1, 1).count(), 4);
+    }
+
+    #[test]
+    fn wrong_and_missing_flags() {
+        let mut board = board(&["*..", "..*", "*.."]);
+        assert_eq!(board.wrong_flag_count(), 0);
+        assert_eq!(board.missing_flag_count(), 3);
+
+        board.flag_cell(0, 0);
+        board.flag_cell(0, 1);
+        board.flag_cell(2, 2);
+        assert_eq!(board.wrong_flag_count(), 2);
+        assert_eq!(board.missing_flag_count(), 2);
+    }
+
+    #[test]
+    fn alternate_display_labels_rows_and_columns() {
+        let board = Board::new_seeded(12, 3, 1, 0);
+
+        let labelled = format!("{:#}", board);
+        let mut lines = labelled.lines();
+        let header: Vec<&str> = lines.next().unwrap().split_whitespace().collect();
+        let columns: Vec<String> = (0..12).map(|col| col.to_string()).collect();
+        assert_eq!(header, columns);
+        assert!(lines.nth(1).unwrap().starts_with("0 |"));
+
+        let plain = format!("{}", board);
+        assert!(plain.starts_with("-----"));
+        assert_eq!(plain.lines().count(), 7);
+        assert_eq!(labelled.lines().count(), 8);
+    }
+
+    #[test]
+    fn reveal_outcome_revealed() {
+        let mut board = board(&["*..", "..."]);
+        assert_eq!(
+            board.reveal_cell(0, 1),
+            RevealOutcome::Revealed(vec![(0, 1)])
+        );
+    }
+
+    #[test]
+    fn reveal_outcome_hit_mine() {
+        let mut board = board(&["*..", "..."]);
+        assert_eq!(board.reveal_cell(0, 0), RevealOutcome::HitMine(0, 0));
+    }
+
+    #[test]
+    fn reveal_outcome_no_op() {
+        let mut board = board(&["*..", "..."]);
+        board.reveal_cell(0, 1);
+        board.flag_cell(1, 0);
+
+        assert_eq!(board.reveal_cell(0, 1), RevealOutcome::NoOp);
+        assert_eq!(board.reveal_cell(1, 0), RevealOutcome::NoOp);
+        assert_eq!(board.reveal_cell(5, 5), RevealOutcome::NoOp);
+    }
+
+    #[test]
+    fn reveal_outcome_game_over() {
+        let mut board = board(&["*..", "..."]);
+        board.reveal_cell(0, 0);
+        assert_eq!(board.reveal_cell(0, 2), RevealOutcome::GameOver);
+    }
+
+    // Collects what the current thread logs, so tests running alongside don't mix in.
+    #[cfg(feature = "std")]
+    struct CaptureLogger;
+
+    #[cfg(feature = "std")]
+    std::thread_local! {
+        static CAPTURED: core::cell::RefCell<Vec<String>> = Default::default();
+    }
+
+    #[cfg(feature = "std")]
+    impl log::Log for CaptureLogger {
+        fn enabled(&self, _: &log::Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record) {
+            CAPTURED.with(|captured| captured.borrow_mut().push(record.args().to_string()));
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn moves_only_log_at_debug_and_below() {
+        let _ = log::set_logger(&CaptureLogger);
+        let captured = || CAPTURED.with(|captured| captured.borrow_mut().split_off(0));
+        let mut lost = board(&["*..", "..."]);
+
+        log::set_max_level(log::LevelFilter::Info);
+        lost.reveal_cell(5, 5);
+        lost.reveal_cell(0, 0);
+        assert!(captured().is_empty());
+
+        log::set_max_level(log::LevelFilter::Debug);
+        let mut board = board(&["*..", "..."]);
+        board.flag_cell(5, 5);
+        assert_eq!(captured(), vec!["flag out of bounds: (5, 5)"]);
+        log::set_max_level(log::LevelFilter::Off);
+    }
+
+    #[test]
+    fn flagging_after_a_loss_changes_nothing() {
+        let mut board = board(&["*..", "...", "..."]);
+        board.reveal_cell(0, 0);
+        assert!(board.is_lost());
+
+        let before: Vec<CellState> = board.iter_cells().map(|(_, _, cell)| cell).collect();
+        for (row, col) in [(0, 0), (1, 1), (2, 2)].iter().copied() {
+            assert_eq!(board.flag_cell(row, col), FlagOutcome::Ignored);
+        }
+        let after: Vec<CellState> = board.iter_cells().map(|(_, _, cell)| cell).collect();
+        assert_eq!(before, after);
+        assert_eq!(board.flags_placed(), 0);
+    }
+
+    #[test]
+    fn flood_styles_reveal_with_and_without_the_border() {
+        let revealed = |flood_style| {
+            let mut board = board(&["..*..", "..*..", "..*.."]).with_flood_style(flood_style);
+            let mut cells = match board.reveal_cell(0, 0) {
+                RevealOutcome::Revealed(cells) => cells,
+                outcome => panic!("unexpected {:?}", outcome),
+            };
+            cells.sort();
+            cells
+        };
+
+        assert_eq!(
+            revealed(FloodStyle::IncludeBorder),
+            vec![(0, 0), (0, 1), (1, 0), (1, 1), (2, 0), (2, 1)]
+        );
+        assert_eq!(revealed(FloodStyle::ZeroOnly), vec![(0, 0), (1, 0), (2, 0)]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn mine_positions_match_the_requested_count() {
+        let mut board = Board::new(9, 9, 10);
+        board.reveal_cell(4, 4);
+
+        let mines = board.mine_positions();
+        assert_eq!(mines.len(), 10);
+        assert!(mines.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(mines
+            .iter()
+            .all(|&(row, col)| matches!(board.cell(row, col), CellState::Mine(..))));
+    }
+
+    #[test]
+    fn resize_keeps_the_mines_that_fit() {
+        let mut board = board(&["*..", "...", "..*"]);
+
+        board.resize(5, 5);
+        assert_eq!(board.mine_positions(), vec![(0, 0), (2, 2)]);
+        assert_eq!(board.mine_count(), 2);
+        assert_eq!(
+            board.cell(1, 1),
+            CellState::Neighbours(false, Marker::None, 2)
+        );
+        assert_eq!(
+            board.cell(3, 3),
+            CellState::Neighbours(false, Marker::None, 1)
+        );
+        assert_eq!(
+            board.cell(4, 4),
+            CellState::Neighbours(false, Marker::None, 0)
+        );
+
+        board.set_mine(4, 4, true);
+        board.resize(3, 3);
+        assert_eq!(board.mine_positions(), vec![(0, 0), (2, 2)]);
+        assert_eq!(board.mine_count(), 2);
+        assert_eq!(
+            board.cell(2, 1),
+            CellState::Neighbours(false, Marker::None, 1)
+        );
+        assert_eq!(
+            board.cell(1, 1),
+            CellState::Neighbours(false, Marker::None, 2)
+        );
+    }
+
+    #[test]
+    fn resize_before_placing_keeps_the_mine_count() {
+        let mut board = Board::new_seeded(9, 9, 10, 1);
+
+        board.resize(16, 16);
+        assert!(!board.mines_placed());
+        assert_eq!(board.mine_count(), 10);
+
+        board.resize(3, 3);
+        assert_eq!(board.mine_count(), 8);
+        board.reveal_cell(1, 1);
+        assert_eq!(board.mine_positions().len(), 8);
+    }
+
+    #[test]
+    fn independently_built_boards_are_equal() {
+        let mut first = board(&["*..", "...", ".*."]);
+        let mut second = board(&["*..", "...", ".*."]);
+        assert_eq!(first, second);
+
+        first.reveal_cell(0, 2);
+        second.reveal_cell(0, 2);
+        assert_eq!(first, second);
+
+        second.flag_cell(0, 0);
+        assert_ne!(first, second);
+        assert_ne!(first, board(&["*..", "...", "..*"]));
+    }
+
+    #[test]
+    fn seeded_coordinate_selection_is_repeatable() {
+        let coords: Vec<(usize, usize)> = (0..5)
+            .flat_map(|row| (0..4).map(move |col| (row, col)))
+            .collect();
+        let select = |number: usize| {
+            let mut shuffled = coords.clone();
+            let mut rng = StdRng::seed_from_u64(7);
+            select_random_coords(&mut shuffled, number, &mut rng).to_vec()
+        };
+
+        let picked = select(6);
+        assert_eq!(picked, select(6));
+        assert_eq!(picked, vec![(1, 3), (3, 3), (0, 2), (3, 2), (0, 3), (1, 1)]);
+        for (i, coord) in picked.iter().enumerate() {
+            assert!(coords.contains(coord));
+            assert!(!picked[i + 1..].contains(coord));
+        }
+        assert_eq!(select(30).len(), coords.len());
+    }
+
+    #[test]
+    fn flag_outcomes() {
+        let mut board = board(&["*..", "..*", "..."]);
+        board.reveal_cell(2, 0);
+
+        assert_eq!(board.flag_cell(0, 0), FlagOutcome::Added);
+        assert_eq!(board.cell(0, 0), CellState::Mine(false, Marker::Flag));
+        assert_eq!(board.flag_cell(0, 0), FlagOutcome::Removed);
+        assert_eq!(board.cell(0, 0), CellState::Mine(false, Marker::Question));
+        assert_eq!(board.flag_cell(0, 0), FlagOutcome::Ignored);
+        assert_eq!(board.cell(0, 0), CellState::Mine(false, Marker::None));
+
+        // Revealed cells and ones off the board can't be flagged.
+        assert_eq!(board.flag_cell(2, 0), FlagOutcome::Ignored);
+        assert_eq!(
+            board.cell(2, 0),
+            CellState::Neighbours(true, Marker::None, 0)
+        );
+        assert_eq!(board.flag_cell(3, 0), FlagOutcome::Ignored);
+        assert_eq!(board.flags_placed(), 0);
+    }
+
+    #[test]
+    fn with_mines_counts_neighbours() {
+        let board = Board::with_mines(4, 3, &[(0, 0), (1, 2)]).unwrap();
+
+        assert_eq!(board.mine_count(), 2);
+        assert_eq!(board.mine_positions(), vec![(0, 0), (1, 2)]);
+        let counts: Vec<u8> = board
+            .iter_cells()
+            .map(|(_, _, cell)| match cell {
+                CellState::Neighbours(_, _, count) => count,
+                CellState::Mine(..) => 9,
+            })
+            .collect();
+        assert_eq!(counts, vec![9, 2, 1, 1, 1, 2, 9, 1, 0, 1, 1, 1]);
+
+        assert_eq!(
+            Board::with_mines(4, 3, &[(0, 4)]),
+            Err(BoardError::MineOutOfBounds(0, 4))
+        );
+        assert_eq!(
+            Board::with_mines(4, 3, &[(2, 3), (2, 3)]),
+            Err(BoardError::DuplicateMine(2, 3))
+        );
+    }
+
+    #[test]
+    fn last_safe_reveal_wins_and_flags_the_mines() {
+        let mut board = board(&["*.", ".*"]);
+        board.flag_cell(1, 1);
+        board.flag_cell(1, 1);
+
+        board.reveal_cell(0, 1);
+        assert_eq!(board.state(), GameState::Playing);
+        assert_eq!(board.flags_placed(), 0);
+
+        board.reveal_cell(1, 0);
+        assert_eq!(board.state(), GameState::Won);
+        assert_eq!(board.cell(0, 0), CellState::Mine(false, Marker::Flag));
+        assert_eq!(board.cell(1, 1), CellState::Mine(false, Marker::Flag));
+        assert_eq!(board.flags_placed(), 2);
+        assert_eq!(board.remaining_mines(), 0);
+    }
+
+    #[test]
+    fn recompute_count_follows_edits_to_the_layout() {
+        let mut board = board(&["...", "...", "..."]);
+        let centre = board.index(1, 1);
+
+        board.grid[centre] = CellState::Mine(false, Marker::None);
+        assert_eq!(
+            board.cell(0, 0),
+            CellState::Neighbours(false, Marker::None, 0)
+        );
+        for (row, col) in board.neighbours(1, 1).collect::<Vec<_>>() {
+            board.recompute_count(row, col);
+            assert_eq!(
+                board.cell(row, col),
+                CellState::Neighbours(false, Marker::None, 1)
+            );
+        }
+        board.recompute_count(1, 1);
+        board.recompute_count(3, 3);
+        assert_eq!(board.cell(1, 1), CellState::Mine(false, Marker::None));
+
+        board.grid[centre] = CellState::Neighbours(false, Marker::None, 0);
+        board.recompute_count(2, 0);
+        assert_eq!(
+            board.cell(2, 0),
+            CellState::Neighbours(false, Marker::None, 0)
+        );
+        assert_eq!(
+            board.cell(2, 1),
+            CellState::Neighbours(false, Marker::None, 1)
+        );
+    }
+
+    #[test]
+    fn set_mine_updates_the_surrounding_counts() {
+        let mut board = board(&["....", "....", "....", "...*"]);
+        assert_eq!(board.neighbours(1, 1).count(), 8);
+
+        board.set_mine(1, 1, true);
+        assert_eq!(board.mine_count(), 2);
+        assert_eq!(board.cell(1, 1), CellState::Mine(false, Marker::None));
+        for (row, col) in board.neighbours(1, 1) {
+            // (2, 2) also borders the mine at (3, 3).
+            let count = if (row, col) == (2, 2) { 2 } else { 1 };
+            assert_eq!(
+                board.cell(row, col),
+                CellState::Neighbours(false, Marker::None, count)
+            );
+        }
+
+        board.set_mine(1, 1, false);
+        assert_eq!(board.mine_count(), 1);
+        assert_eq!(
+            board.cell(1, 1),
+            CellState::Neighbours(false, Marker::None, 0)
+        );
+        for (row, col) in board.neighbours(1, 1) {
+            let count = if (row, col) == (2, 2) { 1 } else { 0 };
+            assert_eq!(
+                board.cell(row, col),
+                CellState::Neighbours(false, Marker::None, count)
+            );
+        }
+    }
+
+    #[test]
+    fn editing_an_unplaced_board_starts_from_no_mines() {
+        let mut board = Board::new_seeded(5, 5, 10, 0);
+        board.set_mine(2, 2, true);
+        assert!(board.mines_placed());
+        assert_eq!(board.mine_count(), 1);
+        assert_eq!(board.mine_positions(), vec![(2, 2)]);
+
+        let mut cleared = board.clone();
+        cleared.clear_mines();
+        assert!(cleared.mines_placed());
+        assert_eq!(cleared.mine_count(), 0);
+        assert!(cleared.mine_positions().is_empty());
+        cleared.set_mine(0, 0, true);
+        assert_eq!(cleared.mine_count(), 1);
+    }
+
+    #[test]
+    fn full_view_shows_what_display_hides() {
+        let mut board = board(&["*..", "...", "..*"]);
+        board.reveal_cell(0, 2);
+        let rows = |text: String| -> Vec<String> {
+            text.lines().skip(1).step_by(2).map(String::from).collect()
+        };
+
+        assert_eq!(
+            rows(format!("{}", board)),
+            vec!["|   | 1 | 0 |", "|   | 2 | 1 |", "|   |   |   |"]
+        );
+        assert_eq!(
+            rows(format!("{}", board.full_view())),
+            vec!["| X | 1 | 0 |", "| 1 | 2 | 1 |", "| 0 | 1 | X |"]
+        );
+        assert_eq!(board.state(), GameState::Playing);
+        assert_eq!(board.revealed_count(), 4);
+    }
+
+    #[test]
+    fn safe_chords_refuse_a_misplaced_flag() {
+        let misflagged = |chord_safety| {
+            let mut board = board(&["*..", "...", "..."]).with_chord_safety(chord_safety);
+            board.reveal_cell(1, 1);
+            board.flag_cell(0, 1);
+            board.chord_cell(1, 1);
+            board
+        };
+
+        let strict = misflagged(ChordSafety::Strict);
+        assert!(strict.is_lost());
+
+        let safe = misflagged(ChordSafety::Safe);
+        assert_eq!(safe.state(), GameState::Playing);
+        assert_eq!(safe.revealed_count(), 1);
+        assert_eq!(safe.cell(0, 0), CellState::Mine(false, Marker::None));
+
+        let mut flagged = board(&["*..", "...", "..."]).with_chord_safety(ChordSafety::Safe);
+        flagged.reveal_cell(1, 1);
+        flagged.flag_cell(0, 0);
+        flagged.chord_cell(1, 1);
+        assert!(flagged.is_won());
+    }
+
+    #[test]
+    fn percent_complete_from_start_to_finish() {
+        let mut cleared = board(&["*...", "....", "...."]);
+        assert_eq!(cleared.percent_complete(), 0.0);
+
+        cleared.reveal_cell(0, 1);
+        assert!((cleared.percent_complete() - 100.0 / 11.0).abs() < 1e-4);
+
+        cleared.reveal_cell(2, 3);
+        assert!(cleared.is_won());
+        assert_eq!(cleared.percent_complete(), 100.0);
+
+        let all_mines = board(&["**", "**"]);
+        assert_eq!(all_mines.percent_complete(), 100.0);
+    }
+
+    #[test]
+    fn changing_a_clone_leaves_the_original_alone() {
+        let original = board(&["*..", "...", "..."]);
+        let mut copy = original.clone();
+
+        copy.flag_cell(0, 0);
+        copy.reveal_cell(2, 2);
+        assert!(copy.is_won());
+
+        assert_eq!(original.state(), GameState::Playing);
+        assert_eq!(original.revealed_count(), 0);
+        assert_eq!(original.cell(0, 0), CellState::Mine(false, Marker::None));
+        assert_eq!(original, board(&["*..", "...", "..."]));
+    }
+
+    #[test]
+    fn flag_cap_refuses_extra_flags_until_one_comes_off() {
+        let mut board = board(&["*..", "..*", "..."]).with_max_flags(Some(2));
+
+        assert_eq!(board.flag_cell(0, 1), FlagOutcome::Added);
+        assert_eq!(board.flag_cell(0, 2), FlagOutcome::Added);
+        assert_eq!(board.flag_cell(1, 0), FlagOutcome::Capped);
+        assert_eq!(
+            board.cell(1, 0),
+            CellState::Neighbours(false, Marker::None, 1)
+        );
+        assert_eq!(board.flags_placed(), 2);
+
+        assert_eq!(board.flag_cell(0, 1), FlagOutcome::Removed);
+        assert_eq!(board.flag_cell(1, 0), FlagOutcome::Added);
+        assert_eq!(board.flags_placed(), 2);
+        assert_eq!(board.flag_cell(0, 1), FlagOutcome::Ignored);
+        assert_eq!(board.flag_cell(0, 1), FlagOutcome::Capped);
+    }
+
+    #[test]
+    fn get_cell_state_shows_what_hides_under_unrevealed_cells() {
+        let mut board = board(&["*..", "...", "..."]);
+        board.flag_cell(1, 1);
+
+        assert_eq!(
+            board.get_cell_state(0, 0),
+            Some(CellState::Mine(false, Marker::None))
+        );
+        assert_eq!(
+            board.get_cell_state(1, 1),
+            Some(CellState::Neighbours(false, Marker::Flag, 1))
+        );
+        assert_eq!(
+            board.get_cell_state(2, 2),
+            Some(CellState::Neighbours(false, Marker::None, 0))
+        );
+        assert_eq!(board.peek_cell(0, 0), CellState::Mine(false, Marker::None));
+        assert_eq!(
+            board.peek_cell(1, 1),
+            CellState::Neighbours(false, Marker::Flag, 1)
+        );
+        assert_eq!(board.revealed_count(), 0);
+    }
+
+    #[test]
+    fn boards_can_draw_their_seed_from_any_rng() {
+        let mut first = Board::try_new_with_rng(9, 9, 10, &mut StdRng::seed_from_u64(3)).unwrap();
+        let mut second = Board::try_new_with_rng(9, 9, 10, &mut StdRng::seed_from_u64(3)).unwrap();
+        assert_eq!(first.seed(), second.seed());
+
+        first.reveal_cell(4, 4);
+        second.reveal_cell(4, 4);
+        assert_eq!(first.mine_positions(), second.mine_positions());
+        assert_eq!(first.mine_positions().len(), 10);
+
+        let mut rng = StdRng::seed_from_u64(3);
+        assert_eq!(
+            Board::try_new_with_rng(0, 9, 10, &mut rng),
+            Err(BoardError::ZeroDimension)
+        );
+        assert_eq!(
+            Board::try_new_with_rng(3, 3, 9, &mut rng),
+            Err(BoardError::TooManyMines)
+        );
+    }
+
+    #[test]
+    fn adjacent_counts_with_mixed_neighbours() {
+        let mut board = board(&["*...", "....", "..*."]);
+        board.reveal_cell(0, 3);
+        board.flag_cell(0, 0);
+        board.flag_cell(2, 1);
+        board.flag_cell(1, 0);
+        board.flag_cell(1, 0);
+
+        // Around (1, 1): three revealed, a flag on a mine, a wrong flag, a question mark and two
+        // plain hidden cells.
+        assert_eq!(board.adjacent_flag_count(1, 1), 2);
+        assert_eq!(board.adjacent_unrevealed_count(1, 1), 5);
+
+        assert_eq!(board.adjacent_flag_count(0, 0), 0);
+        assert_eq!(board.adjacent_unrevealed_count(0, 0), 1);
+        assert_eq!(board.adjacent_flag_count(0, 3), 0);
+        assert_eq!(board.adjacent_unrevealed_count(0, 3), 0);
+    }
+
+    #[test]
+    fn numbers_are_satisfied_by_exactly_their_count_of_flags() {
+        let mut board = board(&["*..", "...", "..."]);
+        board.reveal_cell(1, 1);
+        assert!(!board.is_number_satisfied(1, 1));
+
+        board.flag_cell(0, 0);
+        assert!(board.is_number_satisfied(1, 1));
+
+        board.flag_cell(0, 1);
+        assert!(!board.is_number_satisfied(1, 1));
+
+        // Only revealed numbers count, whatever flags are around them.
+        assert!(!board.is_number_satisfied(1, 0));
+        assert!(!board.is_number_satisfied(0, 0));
+        assert!(!board.is_number_satisfied(5, 5));
+    }
+
+    #[test]
+    fn remaining_mines_can_go_negative() {
+        let mut board = board(&["*..", "..*", "..."]);
+        assert_eq!(board.remaining_mines(), 2);
+
+        board.flag_cell(0, 0);
+        assert_eq!(board.remaining_mines(), 1);
+        board.flag_cell(1, 2);
+        assert_eq!(board.remaining_mines(), 0);
+        assert_eq!(board.remaining_mines_clamped(), 0);
+
+        board.flag_cell(2, 0);
+        board.flag_cell(2, 1);
+        assert_eq!(board.remaining_mines(), -2);
+        assert_eq!(board.remaining_mines_clamped(), 0);
+    }
+
+    #[test]
+    fn auto_flag_does_nothing_while_peeking() {
+        let mut board = board(&["..*", "...", "..."]);
+        board.flag_cell(1, 0);
+        let before = board.clone();
+
+        board.reveal_all();
+        board.auto_flag(2, 0);
+        board.hide_all();
+        assert_eq!(board, before);
+    }
+}
@@ -1,64 +1,326 @@
 use iced::executor;
 use iced::{
-    canvas::{self, Cache, Canvas, Cursor, Event, Geometry, Text},
-    mouse, Application, Color, Column, Command, Container, Element, HorizontalAlignment, Length,
-    Point, Rectangle, Settings, Size, Subscription, VerticalAlignment,
+    canvas::{self, Cache, Canvas, Cursor, Event, Frame, Geometry, Path, Stroke},
+    keyboard, mouse, scrollable, time, Application, Color, Column, Command, Container, Element,
+    HorizontalAlignment, Length, Point, ProgressBar, Rectangle, Row, Scrollable, Settings, Size,
+    Subscription, Text, Vector, VerticalAlignment,
 };
-use rand::thread_rng;
-use std::collections::HashSet;
-use std::fmt;
+use minesweepe_rs::{
+    cli, daily_seed, today, Board, CellState, ChordSafety, Difficulty, FlagOutcome, GameState,
+    Marker, RevealOutcome, Scores, Silent, SoundSink, Topology,
+};
+use std::collections::VecDeque;
+use std::io;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+const SAVE_PATH: &str = "minesweeper-save.json";
+
+// Best times live in the platform's data directory rather than next to the save file.
+fn scores_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("minesweepe-rs").join("scores.json"))
+}
+
+#[derive(Debug, Default)]
+struct Options {
+    difficulty: Difficulty,
+    topology: Topology,
+    animate: bool,
+    fade: bool,
+    colorblind: bool,
+    cheats: bool,
+    practice: bool,
+    heatmap: bool,
+    labels: bool,
+    fit: bool,
+    glyphs: Option<Glyphs>,
+    style: GridStyle,
+    battle: bool,
+    daily: bool,
+    chord_safety: ChordSafety,
+    cap_flags: bool,
+}
+
+impl Options {
+    // Everyone gets the same board with `--daily`.
+    fn seed(&self) -> u64 {
+        if self.daily {
+            let (year, month, day) = today();
+            daily_seed(year, month, day)
+        } else {
+            rand::random()
+        }
+    }
+
+    fn new_board(&self, seed: u64) -> Board {
+        let (width, height, mines) = self.difficulty.parameters();
+        Board::new_seeded(width, height, mines, seed)
+            .with_topology(self.topology)
+            .with_chord_safety(self.chord_safety)
+            .with_max_flags(if self.cap_flags { Some(mines) } else { None })
+    }
+}
+
+const BORDER_ARG: &str = "--border=";
 
 fn main() -> iced::Result {
+    env_logger::init();
+
+    let mut options = Options::default();
+    let mut text_mode = false;
+
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--cli" => text_mode = true,
+            "--torus" => options.topology = Topology::Torus,
+            "--animate" => options.animate = true,
+            "--fade" => options.fade = true,
+            "--colorblind" => options.colorblind = true,
+            "--cheats" => options.cheats = true,
+            "--practice" => options.practice = true,
+            "--heatmap" => options.heatmap = true,
+            "--labels" => options.labels = true,
+            "--fit" => options.fit = true,
+            "--glyphs" => options.glyphs = Some(Glyphs::default()),
+            "--emoji" => options.glyphs = Some(Glyphs::emoji()),
+            "--battle" => options.battle = true,
+            "--daily" => options.daily = true,
+            "--safe-chords" => options.chord_safety = ChordSafety::Safe,
+            "--flat" => options.style.flat = true,
+            "--cap-flags" => options.cap_flags = true,
+            _ if arg.starts_with(BORDER_ARG) => match arg[BORDER_ARG.len()..].parse() {
+                Ok(thickness) => options.style.border_thickness = thickness,
+                Err(_) => {
+                    eprintln!("invalid border thickness: {}", arg);
+                    std::process::exit(1);
+                }
+            },
+            _ => match arg.parse() {
+                Ok(parsed) => options.difficulty = parsed,
+                Err(err) => {
+                    eprintln!("{}", err);
+                    std::process::exit(1);
+                }
+            },
+        }
+    }
+
+    if text_mode {
+        let mut board = options.new_board(options.seed());
+        let stdin = io::stdin();
+        if let Err(err) = cli::run(&mut board, stdin.lock(), io::stdout()) {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     Minesweeper::run(Settings {
         antialiasing: true,
-        ..Settings::default()
+        ..Settings::with_flags(options)
     })
 }
 
 struct Minesweeper {
-    grid: UIGrid,
+    // One board per player, all generated from the same seed.
+    grids: Vec<UIGrid>,
+    scroll: scrollable::State,
+    elapsed_seconds: u64,
+    started: bool,
+    // Whether a win goes towards the best times. Loaded and edited boards don't, since the clock
+    // restarts partway through their games.
+    ranked: bool,
+    practice: bool,
+    scores: Scores,
+    // The player who won a battle first, or the last one standing once the others have lost.
+    winner: Option<usize>,
+    // Frames since the winner was declared, which flash the banner for a while.
+    winner_frame: u8,
+    sound: Box<dyn SoundSink>,
+    paused: bool,
+    // What new boards are built from.
+    options: Options,
 }
 
 #[derive(Debug)]
 enum UIMessage {
     Reveal(usize, usize),
     Flag(usize, usize),
+    // Flags the unmarked cells a right-drag passed over.
+    FlagCells(Vec<(usize, usize)>),
+    Chord(usize, usize),
+    AutoFlag(usize, usize),
+    Zoom(f32),
+    Peek,
+    Undo,
+    Preview(usize, usize),
+    MoveCursor(usize, usize),
+    NewGame,
+    Save,
+    Load,
+    SolveStep,
+    Hint,
+    AutoOpen,
+    // Reveals a random safe cell for a stuck player.
+    RevealSafe,
+    Tick,
+    Frame,
+    TogglePause,
+    CycleDifficulty,
+    ToggleEdit,
+    // Moves the board within the canvas to the given offset.
+    Pan(Vector),
+    // Adds or removes a mine while editing.
+    ToggleMine(usize, usize),
+    // A message from the board of the given player.
+    Player(usize, Box<UIMessage>),
+    // Something that happened in the window, for the keys that aren't tied to a board.
+    Event(iced_native::Event),
 }
 
 impl Application for Minesweeper {
     type Message = UIMessage;
     type Executor = executor::Default;
-    type Flags = ();
+    type Flags = Options;
+
+    fn new(options: Options) -> (Self, Command<Self::Message>) {
+        let seed = options.seed();
+        let players = if options.battle { 2 } else { 1 };
 
-    fn new(_flags: ()) -> (Self, Command<Self::Message>) {
-        let board = Board::new(40, 40, 50);
         (
             Self {
-                grid: UIGrid {
-                    board,
-                    grid_cache: Cache::default(),
-                },
+                grids: (0..players)
+                    .map(|player| {
+                        let mut grid = UIGrid::new(options.new_board(seed), &options);
+                        // Both canvases hear every key, so only the first player's board
+                        // answers to them; the second player uses the mouse.
+                        grid.keyboard = player == 0;
+                        grid
+                    })
+                    .collect(),
+                scroll: scrollable::State::new(),
+                elapsed_seconds: 0,
+                started: false,
+                ranked: true,
+                practice: options.practice,
+                scores: scores_path()
+                    .and_then(|path| Scores::load_json(path).ok())
+                    .unwrap_or_default(),
+                winner: None,
+                winner_frame: 0,
+                sound: Box::new(Silent),
+                paused: false,
+                options,
             },
             Command::none(),
         )
     }
 
+    // iced asks for the title again after every update, so it follows the game as it ends.
     fn title(&self) -> String {
-        "Minesweeper".into()
+        let state = self
+            .grids
+            .iter()
+            .map(|grid| grid.board.state())
+            .find(|&state| state != GameState::Playing)
+            .unwrap_or(GameState::Playing);
+
+        window_title(state, self.elapsed_seconds, self.grids[0].board.seed())
     }
 
     fn update(&mut self, message: Self::Message) -> Command<Self::Message> {
-        println!("Handling message: {:?}", message);
-        self.grid.update(message);
+        log::trace!("handling message: {:?}", message);
+
+        match message {
+            UIMessage::Tick => {
+                if self.timer_running() {
+                    self.elapsed_seconds += 1;
+                }
+            }
+            UIMessage::Frame => {
+                if self.winner.is_some() && self.winner_frame < WINNER_FLASH_FRAMES {
+                    self.winner_frame += 1;
+                }
+                for grid in &mut self.grids {
+                    grid.update(UIMessage::Frame, self.sound.as_mut());
+                }
+            }
+            UIMessage::Player(player, message) => self.update_player(player, *message),
+            UIMessage::Event(event) => {
+                if let Some(message) = game_key(event, self.options.cheats) {
+                    return self.update(message);
+                }
+            }
+            message => self.update_player(0, message),
+        }
+
         Command::none()
     }
 
     fn subscription(&self) -> Subscription<Self::Message> {
-        Subscription::none()
+        let timer = if self.timer_running() {
+            time::every(Duration::from_secs(1)).map(|_| UIMessage::Tick)
+        } else {
+            Subscription::none()
+        };
+
+        let flashing = self.winner.is_some() && self.winner_frame < WINNER_FLASH_FRAMES;
+        let animating = flashing
+            || self.grids.iter().any(|grid| {
+                !grid.pending.is_empty()
+                    || grid.explosion_frame.is_some()
+                    || !grid.fading.is_empty()
+            });
+        let animation = if animating {
+            time::every(FRAME_DURATION).map(|_| UIMessage::Frame)
+        } else {
+            Subscription::none()
+        };
+
+        let keys = iced_native::subscription::events_with(game_key_event);
+
+        Subscription::batch(vec![timer, animation, keys])
     }
 
     fn view(&mut self) -> Element<Self::Message> {
-        let content = Column::new().push(self.grid.view());
+        let mut status = Row::new()
+            .spacing(20)
+            .push(Text::new(format!("Time: {}", self.elapsed_seconds)).size(24));
+
+        if let Some(best) = self.difficulty().and_then(|d| self.scores.best(d)) {
+            status = status.push(Text::new(format!("Best: {}", best)).size(24));
+        }
+
+        if let Some(winner) = self.winner {
+            status = status.push(
+                Text::new(format!("Player {} wins!", winner + 1))
+                    .size(24)
+                    .color(winner_color(self.winner_frame)),
+            );
+        }
+
+        let practice = self.practice;
+        let fit_window = self.grids[0].fit_window;
+        let mut boards = Row::new().spacing(40);
+        for (player, grid) in self.grids.iter_mut().enumerate() {
+            boards = boards.push(
+                grid.view(practice)
+                    .map(move |message| UIMessage::Player(player, Box::new(message))),
+            );
+        }
+
+        // Boards fitted to the window have nothing to scroll.
+        let board: Element<_> = if fit_window {
+            boards.width(Length::Fill).height(Length::Fill).into()
+        } else {
+            Scrollable::new(&mut self.scroll)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .push(boards)
+                .into()
+        };
+
+        let content = Column::new().push(status).push(board);
 
         Container::new(content)
             .width(Length::Fill)
@@ -67,111 +329,1230 @@ impl Application for Minesweeper {
     }
 }
 
+impl Minesweeper {
+    fn update_player(&mut self, player: usize, message: UIMessage) {
+        let was_won = self.grids[player].board.is_won();
+        let was_lost = self.grids[player].board.is_lost();
+        if is_assist(&message) && !self.paused {
+            self.ranked = false;
+        }
+
+        match message {
+            UIMessage::TogglePause => self.set_paused(!self.paused),
+            UIMessage::NewGame => {
+                self.started = false;
+                self.ranked = true;
+                self.elapsed_seconds = 0;
+                self.winner = None;
+                self.set_paused(false);
+                let seed = self.options.seed();
+                for grid in &mut self.grids {
+                    grid.restart(seed);
+                }
+            }
+            UIMessage::CycleDifficulty => {
+                self.started = false;
+                self.ranked = true;
+                self.elapsed_seconds = 0;
+                self.winner = None;
+                self.set_paused(false);
+                self.options.difficulty = self.options.difficulty.next();
+                let seed = self.options.seed();
+                for grid in &mut self.grids {
+                    grid.replace_board(self.options.new_board(seed));
+                }
+            }
+            UIMessage::Load | UIMessage::ToggleEdit => {
+                self.started = false;
+                self.ranked = false;
+                self.elapsed_seconds = 0;
+                self.winner = None;
+                self.set_paused(false);
+                self.grids[player].update(message, self.sound.as_mut());
+            }
+            _ if self.paused && is_move(&message) => {}
+            UIMessage::Reveal(..) | UIMessage::AutoOpen | UIMessage::RevealSafe => {
+                let grid = &self.grids[player];
+                if grid.board.state() == GameState::Playing && !grid.editing {
+                    self.started = true;
+                }
+                self.grids[player].update(message, self.sound.as_mut());
+            }
+            _ => self.grids[player].update(message, self.sound.as_mut()),
+        }
+
+        if !was_won && self.grids[player].board.is_won() {
+            if self.grids.len() == 1 && self.ranked {
+                self.record_score();
+            } else if self.winner.is_none() {
+                self.declare_winner(player);
+            }
+        }
+
+        if !was_lost && self.grids[player].board.is_lost() && self.winner.is_none() {
+            let mut standing = self
+                .grids
+                .iter()
+                .enumerate()
+                .filter(|(_, grid)| !grid.board.is_lost())
+                .map(|(player, _)| player);
+            if let (Some(last), None) = (standing.next(), standing.next()) {
+                self.declare_winner(last);
+            }
+        }
+    }
+
+    fn declare_winner(&mut self, player: usize) {
+        self.winner = Some(player);
+        self.winner_frame = 0;
+    }
+
+    fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+        for grid in &mut self.grids {
+            grid.paused = paused;
+            grid.preview = None;
+            grid.drag = None;
+            grid.redraw();
+        }
+    }
+
+    fn timer_running(&self) -> bool {
+        clock_running(
+            self.started,
+            self.paused,
+            self.grids.iter().map(|grid| grid.board.state()),
+        )
+    }
+
+    fn difficulty(&self) -> Option<Difficulty> {
+        let board = &self.grids[0].board;
+        Difficulty::from_parameters(board.width(), board.height(), board.mine_count())
+    }
+
+    fn record_score(&mut self) {
+        let difficulty = match self.difficulty() {
+            Some(difficulty) => difficulty,
+            None => return,
+        };
+
+        if self.scores.record(difficulty, self.elapsed_seconds) {
+            if let Some(path) = scores_path() {
+                if let Err(err) = self.scores.save_json(&path) {
+                    log::error!("failed to save scores to {}: {}", path.display(), err);
+                }
+            }
+        }
+    }
+}
+
+const CELL_SIZE: f32 = 30.0;
+const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(400);
+// How far, in pixels, the middle button has to move before it pans instead of chording.
+const PAN_THRESHOLD: f32 = 5.0;
+// How much of a cell's size the row and column labels take up.
+const LABEL_MARGIN: f32 = 0.8;
+const MIN_ZOOM: f32 = 0.5;
+const MAX_ZOOM: f32 = 3.0;
+// How much one line of scrolling zooms by.
+const ZOOM_STEP: f32 = 1.1;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct GridStyle {
+    border_thickness: f32,
+    border_color: Color,
+    // Fill each cell once and stroke the gridlines across the whole board, rather than drawing a
+    // border and an inset per cell.
+    flat: bool,
+}
+
+impl Default for GridStyle {
+    fn default() -> Self {
+        Self {
+            border_thickness: 2.0,
+            border_color: Color::from_rgb8(70, 74, 83),
+            flat: false,
+        }
+    }
+}
+
+impl GridStyle {
+    // The part of a cell left once its border is drawn. Each cell only draws its top and left
+    // border; the bottom and right ones come from its neighbours.
+    fn inner_cell(&self, top_left: Point, size: Size) -> (Point, Size) {
+        let thickness = self.border_thickness.min(size.width).min(size.height);
+        (
+            Point::new(top_left.x + thickness, top_left.y + thickness),
+            Size::new(size.width - thickness, size.height - thickness),
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct ColorScheme {
+    hint: Color,
+    pressed: Color,
+    wrong_flag: Color,
+    flagged: Color,
+    mine: Color,
+    question: Color,
+    revealed: Color,
+    unrevealed: Color,
+    // Marks revealed mines with a cross so they don't rely on colour alone.
+    cross_mines: bool,
+}
+
+impl Default for ColorScheme {
+    fn default() -> Self {
+        Self {
+            hint: Color::from_rgb8(120, 200, 255),
+            pressed: Color::from_rgb8(0, 140, 0),
+            wrong_flag: Color::from_rgb8(255, 140, 0),
+            flagged: Color::from_rgb8(255, 255, 122),
+            mine: Color::from_rgb8(255, 0, 0),
+            question: Color::from_rgb8(150, 220, 150),
+            revealed: Color::from_rgb8(189, 189, 189),
+            unrevealed: Color::from_rgb8(0, 200, 0),
+            cross_mines: false,
+        }
+    }
+}
+
+impl ColorScheme {
+    // Blue and orange tones that stay distinguishable with the common kinds of colour blindness.
+    fn colorblind() -> Self {
+        Self {
+            hint: Color::from_rgb8(86, 180, 233),
+            pressed: Color::from_rgb8(0, 80, 140),
+            wrong_flag: Color::from_rgb8(204, 121, 167),
+            flagged: Color::from_rgb8(240, 228, 66),
+            mine: Color::from_rgb8(230, 159, 0),
+            question: Color::from_rgb8(160, 200, 230),
+            unrevealed: Color::from_rgb8(0, 114, 178),
+            cross_mines: true,
+            ..Self::default()
+        }
+    }
+}
+
+// Text drawn on mines, flags and question marks in place of the built-in shapes.
+#[derive(Clone, Debug, PartialEq)]
+struct Glyphs {
+    mine: String,
+    flag: String,
+    question: String,
+}
+
+impl Default for Glyphs {
+    fn default() -> Self {
+        Self {
+            mine: String::from("X"),
+            flag: String::from("F"),
+            question: String::from("?"),
+        }
+    }
+}
+
+impl Glyphs {
+    fn emoji() -> Self {
+        Self {
+            mine: String::from("\u{1f4a3}"),
+            flag: String::from("\u{1f6a9}"),
+            question: String::from("\u{2753}"),
+        }
+    }
+
+    fn for_cell(&self, state: CellState) -> Option<&str> {
+        match state {
+            CellState::Mine(_, Marker::Flag) | CellState::Neighbours(false, Marker::Flag, _) => {
+                Some(&self.flag)
+            }
+            CellState::Mine(true, _) => Some(&self.mine),
+            CellState::Mine(false, Marker::Question)
+            | CellState::Neighbours(false, Marker::Question, _) => Some(&self.question),
+            _ => None,
+        }
+    }
+}
+
+const FRAME_DURATION: Duration = Duration::from_millis(30);
+// Rows per render cache; see `UIGrid::band_caches`.
+const BAND_ROWS: usize = 8;
+const CELLS_PER_FRAME: usize = 8;
+const EXPLOSION_FRAMES: u8 = 20;
+// How long the winner banner flashes before it settles, and how many frames each colour lasts.
+const WINNER_FLASH_FRAMES: u8 = 100;
+const WINNER_FLASH_PERIOD: u8 = 8;
+// How much later each cell further from the click starts to fade in, and how long it takes.
+const FADE_DELAY_PER_CELL: Duration = Duration::from_millis(40);
+const FADE_DURATION: Duration = Duration::from_millis(300);
+
 struct UIGrid {
     board: Board,
+    hint: Option<(usize, usize)>,
+    cursor: (usize, usize),
+    preview: Option<(usize, usize)>,
+    // Whether keys act on this board, see `Minesweeper::new`.
+    keyboard: bool,
+    shift_held: bool,
+    // Where the right button was last seen during a drag, relative to the grid's top left.
+    drag: Option<Point>,
+    // Where the middle button was last seen while held, and whether it's panning rather than
+    // previewing a chord.
+    middle_drag: Option<(Point, bool)>,
+    // When and where the left button was last pressed, to spot double clicks.
+    last_click: Option<(Instant, (usize, usize))>,
+    // How far the board has been panned from its resting place.
+    offset: Vector,
+    // Covers the board so it can't be studied while the clock is stopped.
+    paused: bool,
+    // Clicks place and remove mines on a blank board instead of playing.
+    editing: bool,
+    zoom: f32,
+    show_labels: bool,
+    // Scale square cells to fill the canvas instead of using a fixed size, centering the grid.
+    fit_window: bool,
+    cheats: bool,
+    // Hovering a hidden cell shows what's underneath it.
+    practice: bool,
+    // Tints the hidden cells along the frontier by how likely they are to be mines.
+    heatmap: bool,
+    animate: bool,
+    // Fade flood reveals in outward from the click.
+    fade: bool,
+    colors: ColorScheme,
+    glyphs: Option<Glyphs>,
+    style: GridStyle,
+    // The mine that lost the game and how far through its pulse it is.
+    explosion_frame: Option<(usize, usize, u8)>,
+    // Revealed cells still drawn as hidden while the flood animates.
+    pending: VecDeque<(usize, usize)>,
+    // The cells of the latest flood still fading in, with when each starts to.
+    fading: Vec<((usize, usize), Instant)>,
+    // How many random safe cells the player has asked for this game.
+    safe_reveals: usize,
 
-    grid_cache: Cache,
+    // One cache per band of `BAND_ROWS` rows, so a move only redraws the bands it touched.
+    band_caches: Vec<Cache>,
+    // The gridlines of a flat style, which only change with the layout.
+    lines_cache: Cache,
+    // The cells as the caches last drew them.
+    drawn: Vec<CellState>,
+    // The cells `Board::has_forced_guess` was last asked about, and its answer.
+    forced_guess: Option<(Vec<CellState>, bool)>,
 }
 
 impl UIGrid {
-    pub fn view<'a>(&'a mut self) -> Element<'a, UIMessage> {
-        Canvas::new(self)
-            .width(Length::Fill)
-            .height(Length::Fill)
+    fn new(board: Board, options: &Options) -> Self {
+        let mut grid = Self {
+            board,
+            hint: None,
+            cursor: (0, 0),
+            preview: None,
+            keyboard: true,
+            shift_held: false,
+            drag: None,
+            middle_drag: None,
+            last_click: None,
+            offset: Vector::default(),
+            paused: false,
+            editing: false,
+            zoom: 1.0,
+            show_labels: options.labels,
+            fit_window: options.fit,
+            cheats: options.cheats,
+            practice: options.practice,
+            heatmap: options.heatmap,
+            animate: options.animate,
+            fade: options.fade,
+            style: options.style,
+            glyphs: options.glyphs.clone(),
+            colors: if options.colorblind {
+                ColorScheme::colorblind()
+            } else {
+                ColorScheme::default()
+            },
+            explosion_frame: None,
+            pending: VecDeque::new(),
+            fading: Vec::new(),
+            safe_reveals: 0,
+            band_caches: Vec::new(),
+            lines_cache: Cache::new(),
+            drawn: Vec::new(),
+            forced_guess: None,
+        };
+        grid.redraw();
+        grid
+    }
+
+    pub fn view<'a>(&'a mut self, practice: bool) -> Element<'a, UIMessage> {
+        let mines_left = self.board.remaining_mines_clamped();
+
+        let mut status = Row::new()
+            .spacing(20)
+            .push(Text::new(format!("Mines: {}", mines_left)).size(24))
+            .push(
+                Text::new(format!(
+                    "{} / {} revealed",
+                    self.board.revealed_count() - self.pending.len(),
+                    self.board.safe_cell_count()
+                ))
+                .size(24),
+            )
+            .push(
+                ProgressBar::new(0.0..=100.0, self.board.percent_complete())
+                    .width(Length::Units(150))
+                    .height(Length::Units(24)),
+            );
+
+        if self.safe_reveals > 0 {
+            status = status
+                .push(Text::new(format!("Safe reveals used: {}", self.safe_reveals)).size(24));
+        }
+
+        if practice {
+            status = status
+                .push(Text::new(format!("Wrong flags: {}", self.board.wrong_flag_count())).size(24))
+                .push(
+                    Text::new(format!(
+                        "Unflagged mines: {}",
+                        self.board.missing_flag_count()
+                    ))
+                    .size(24),
+                );
+        }
+
+        let mut content = Column::new().push(status);
+        if self.has_forced_guess() {
+            content = content.push(
+                Text::new("No safe move left: you'll have to guess")
+                    .size(20)
+                    .color(Color::from_rgb8(255, 140, 0)),
+            );
+        }
+
+        let (width, height) = if self.fit_window {
+            (Length::Fill, Length::Fill)
+        } else {
+            let cell_size = CELL_SIZE * self.zoom;
+            let margin = self.margin(cell_size);
+            (
+                Length::Units((margin + self.board.width() as f32 * cell_size) as u16),
+                Length::Units((margin + self.board.height() as f32 * cell_size) as u16),
+            )
+        };
+
+        content
+            .push(Canvas::new(self).width(width).height(height))
             .into()
     }
 
-    pub fn update(&mut self, message: UIMessage) {
+    // Runs the solver only when the cells have changed since it last did, rather than on every
+    // view.
+    fn has_forced_guess(&mut self) -> bool {
+        let cells: Vec<CellState> = self.board.iter_cells().map(|(_, _, cell)| cell).collect();
+        if let Some((seen, forced)) = &self.forced_guess {
+            if *seen == cells {
+                return *forced;
+            }
+        }
+
+        let forced = self.board.has_forced_guess();
+        self.forced_guess = Some((cells, forced));
+        forced
+    }
+
+    fn restart(&mut self, seed: u64) {
+        self.board.reset_seeded(seed);
+        self.safe_reveals = 0;
+        self.hint = None;
+        self.preview = None;
+        self.pending.clear();
+        self.fading.clear();
+        self.explosion_frame = None;
+        self.redraw();
+    }
+
+    fn replace_board(&mut self, board: Board) {
+        self.board = board;
+        self.safe_reveals = 0;
+        self.editing = false;
+        self.hint = None;
+        self.preview = None;
+        self.pending.clear();
+        self.fading.clear();
+        self.explosion_frame = None;
+        self.cursor = (0, 0);
+        self.redraw();
+    }
+
+    pub fn update(&mut self, message: UIMessage, sound: &mut dyn SoundSink) {
+        let was_won = self.board.is_won();
+        let was_lost = self.board.is_lost();
+        let state = self.board.state();
+        let hint = self.hint;
+        let preview = self.preview;
+
         match message {
+            UIMessage::Save => {
+                if let Err(err) = self.board.save_json(SAVE_PATH) {
+                    log::error!("failed to save game to {}: {}", SAVE_PATH, err);
+                }
+            }
+            UIMessage::Load => match Board::load_json(SAVE_PATH) {
+                Ok(board) => self.replace_board(board),
+                Err(err) => log::error!("failed to load game from {}: {}", SAVE_PATH, err),
+            },
+            // `Minesweeper` starts new games, so that every board gets the same seed.
+            UIMessage::NewGame
+            | UIMessage::Tick
+            | UIMessage::TogglePause
+            | UIMessage::CycleDifficulty
+            | UIMessage::Player(..)
+            | UIMessage::Event(..) => {}
+            UIMessage::ToggleEdit => {
+                // Designs start from an empty board and are played as they are once done.
+                self.editing = !self.editing;
+                if self.editing {
+                    self.board.clear_mines();
+                    self.hint = None;
+                    self.pending.clear();
+                    self.fading.clear();
+                    self.explosion_frame = None;
+                }
+                self.redraw();
+            }
+            UIMessage::ToggleMine(row, col) => {
+                if let Some(cell) = self.board.get_cell_state(row, col) {
+                    let is_mine = matches!(cell, CellState::Mine(..));
+                    self.board.set_mine(row, col, !is_mine);
+                }
+            }
+            _ if self.editing && is_move(&message) => {}
+            UIMessage::Zoom(lines) => {
+                self.zoom = (self.zoom * ZOOM_STEP.powf(lines))
+                    .max(MIN_ZOOM)
+                    .min(MAX_ZOOM);
+                self.redraw();
+            }
+            UIMessage::Pan(offset) => {
+                self.offset = offset;
+                self.preview = None;
+                self.redraw();
+            }
+            UIMessage::Frame => {
+                let count = CELLS_PER_FRAME.min(self.pending.len());
+                let mut cells: Vec<(usize, usize)> = self.pending.drain(..count).collect();
+                if let Some((row, col, _)) = self.explosion_frame {
+                    cells.push((row, col));
+                }
+                self.explosion_frame = self
+                    .explosion_frame
+                    .map(|(row, col, frame)| (row, col, frame + 1))
+                    .filter(|&(_, _, frame)| frame < EXPLOSION_FRAMES);
+                self.redraw_cells(cells);
+
+                let now = Instant::now();
+                self.fading
+                    .retain(|&(_, start)| now.saturating_duration_since(start) < FADE_DURATION);
+            }
+            UIMessage::MoveCursor(row, col) => {
+                self.cursor = (row, col);
+            }
+            UIMessage::Peek => {
+                if self.board.is_peeking() {
+                    self.board.hide_all();
+                } else {
+                    self.board.reveal_all();
+                }
+                self.redraw();
+            }
+            UIMessage::Undo => {
+                if !self.board.is_peeking() && self.board.undo() {
+                    self.hint = None;
+                    self.pending.clear();
+                    self.fading.clear();
+                    self.explosion_frame = None;
+                    self.redraw();
+                }
+            }
+            _ if self.board.is_won() || self.board.is_lost() || self.board.is_peeking() => {}
             UIMessage::Reveal(row, col) => {
-                self.board.reveal_cell(row, col);
-                self.grid_cache.clear();
+                self.focus(row, col);
+                match self.board.reveal_cell(row, col) {
+                    RevealOutcome::Revealed(cells) => {
+                        sound.on_reveal();
+                        if self.fade {
+                            let now = Instant::now();
+                            self.fading = cells
+                                .iter()
+                                .map(|&cell| {
+                                    let distance = reveal_distance((row, col), cell);
+                                    (cell, now + FADE_DELAY_PER_CELL.mul_f32(distance))
+                                })
+                                .collect();
+                        }
+                        if self.animate {
+                            self.pending.extend(cells);
+                        }
+                    }
+                    RevealOutcome::HitMine(row, col) => self.explosion_frame = Some((row, col, 0)),
+                    _ => {}
+                }
+                self.hint = None;
             }
             UIMessage::Flag(row, col) => {
-                self.board.flag_cell(row, col);
-                self.grid_cache.clear();
+                self.focus(row, col);
+                if matches!(
+                    self.board.flag_cell(row, col),
+                    FlagOutcome::Added | FlagOutcome::Removed
+                ) {
+                    sound.on_flag();
+                }
+                self.hint = None;
+            }
+            UIMessage::FlagCells(cells) => {
+                for (row, col) in cells {
+                    if let Some(CellState::Mine(false, Marker::None))
+                    | Some(CellState::Neighbours(false, Marker::None, _)) =
+                        self.board.get_cell_state(row, col)
+                    {
+                        self.board.flag_cell(row, col);
+                        sound.on_flag();
+                    }
+                }
+                self.hint = None;
+            }
+            UIMessage::Preview(row, col) => {
+                self.preview = Some((row, col));
+            }
+            UIMessage::Chord(row, col) => {
+                self.preview = None;
+                self.focus(row, col);
+                let revealed = self.board.revealed_count();
+                self.board.chord_cell(row, col);
+                if !self.board.is_lost() && self.board.revealed_count() != revealed {
+                    sound.on_reveal();
+                }
+                if self.board.is_lost() {
+                    // Any unflagged mine around the number is one the chord set off.
+                    let board = &self.board;
+                    self.explosion_frame = board
+                        .neighbours(row, col)
+                        .find(|&(o_row, o_col)| {
+                            matches!(
+                                board.cell(o_row, o_col),
+                                CellState::Mine(true, Marker::None)
+                                    | CellState::Mine(true, Marker::Question)
+                            )
+                        })
+                        .map(|(o_row, o_col)| (o_row, o_col, 0));
+                }
+                self.hint = None;
+            }
+            UIMessage::AutoFlag(row, col) => {
+                self.focus(row, col);
+                let flags = self.board.flags_placed();
+                self.board.auto_flag(row, col);
+                if self.board.flags_placed() != flags {
+                    sound.on_flag();
+                }
+                self.hint = None;
+            }
+            UIMessage::SolveStep => {
+                if self.board.solve_step() {
+                    self.hint = None;
+                }
+            }
+            UIMessage::Hint => {
+                self.hint = self.board.safest_unrevealed();
+            }
+            UIMessage::AutoOpen => {
+                if let Some((row, col)) = self.board.best_opening() {
+                    return self.update(UIMessage::Reveal(row, col), sound);
+                }
+            }
+            UIMessage::RevealSafe => {
+                if let Some((row, col)) = self.board.reveal_random_safe(&mut rand::thread_rng()) {
+                    self.safe_reveals += 1;
+                    self.focus(row, col);
+                    sound.on_reveal();
+                    self.hint = None;
+                }
+            }
+        }
+
+        self.refresh(state, hint, preview);
+
+        if !was_won && self.board.is_won() {
+            sound.on_win();
+        }
+        if !was_lost && self.board.is_lost() {
+            sound.on_lose();
+        }
+    }
+
+    // Throws away every cached band, for changes that affect the whole board.
+    fn redraw(&mut self) {
+        let bands = (self.board.height() + BAND_ROWS - 1) / BAND_ROWS;
+        self.band_caches = (0..bands).map(|_| Cache::new()).collect();
+        self.lines_cache.clear();
+        self.drawn = self.board.iter_cells().map(|(_, _, cell)| cell).collect();
+    }
+
+    fn redraw_cells(&mut self, cells: impl IntoIterator<Item = (usize, usize)>) {
+        for (row, _) in cells {
+            if let Some(cache) = self.band_caches.get_mut(row / BAND_ROWS) {
+                cache.clear();
+            }
+        }
+    }
+
+    // Redraws the cells that look different since the last update: those the board changed, and
+    // those under the hint or the chord preview if either moved.
+    fn refresh(
+        &mut self,
+        state: GameState,
+        hint: Option<(usize, usize)>,
+        preview: Option<(usize, usize)>,
+    ) {
+        if self.board.state() != state
+            || self.drawn.len() != self.board.width() * self.board.height()
+        {
+            return self.redraw();
+        }
+
+        let cells = self.changed_cells(hint, preview);
+        self.redraw_cells(cells);
+    }
+
+    // The cells to redraw for `refresh`, given the hint and preview the last draw used. Marks the
+    // board's current cells as drawn.
+    fn changed_cells(
+        &mut self,
+        hint: Option<(usize, usize)>,
+        preview: Option<(usize, usize)>,
+    ) -> Vec<(usize, usize)> {
+        let mut cells = Vec::new();
+        if hint != self.hint {
+            cells.extend(hint);
+            cells.extend(self.hint);
+        }
+        if preview != self.preview {
+            for (row, col) in preview.into_iter().chain(self.preview) {
+                cells.extend(self.board.neighbours(row, col));
             }
         }
+        for (index, (row, col, cell)) in self.board.iter_cells().enumerate() {
+            if self.drawn[index] != cell {
+                self.drawn[index] = cell;
+                cells.push((row, col));
+                // The numbers around a flag dim once they're satisfied.
+                cells.extend(self.board.neighbours(row, col));
+            }
+        }
+
+        cells
+    }
+
+    fn focus(&mut self, row: usize, col: usize) {
+        if self.board.get_cell_state(row, col).is_some() {
+            self.cursor = (row, col);
+        }
+    }
+
+    // Maps a position on the canvas to fractional (col, row) cell coordinates.
+    pub fn project(&self, position: Point, bounds: Size) -> Point {
+        let (cell_size, _) = self.layout(bounds);
+        let position = self.grid_position(position, bounds);
+        Point::new(position.x / cell_size, position.y / cell_size)
+    }
+
+    // In practice mode, what's under the hidden cell at `position`: its true count or "mine".
+    fn tooltip(&self, position: Point, bounds: Size) -> Option<String> {
+        if !self.practice
+            || self.paused
+            || self.board.state() != GameState::Playing
+            || !self.board.mines_placed()
+        {
+            return None;
+        }
+
+        let cell = self.project(position, bounds);
+        if cell.x < 0.0 || cell.y < 0.0 {
+            return None;
+        }
+        let (row, col) = (cell.y as usize, cell.x as usize);
+        if row >= self.board.height() || col >= self.board.width() {
+            return None;
+        }
+
+        match self.board.peek_cell(row, col) {
+            CellState::Mine(false, _) => Some(String::from("mine")),
+            CellState::Neighbours(false, _, count) => Some(count.to_string()),
+            _ => None,
+        }
+    }
+
+    fn grid_position(&self, position: Point, bounds: Size) -> Point {
+        let (_, origin) = self.layout(bounds);
+        Point::new(position.x - origin.x, position.y - origin.y)
     }
 
-    pub fn project(&self, position: Point, size: Size) -> Point {
-        let cell_w = size.width / self.board.width() as f32;
-        let cell_h = size.height / self.board.height() as f32;
-        Point::new(position.x / cell_w, position.y / cell_h)
+    // The size of a cell and the top left of the grid itself, past the labels. The grid is
+    // centred in the canvas, which only matters when it's fitted to the window.
+    fn layout(&self, bounds: Size) -> (f32, Point) {
+        let labels = if self.show_labels { LABEL_MARGIN } else { 0.0 };
+        let columns = self.board.width() as f32 + labels;
+        let rows = self.board.height() as f32 + labels;
+
+        let cell_size = if self.fit_window {
+            (bounds.width / columns).min(bounds.height / rows) * self.zoom
+        } else {
+            CELL_SIZE * self.zoom
+        };
+        let margin = self.margin(cell_size);
+
+        let origin = Point::new(
+            ((bounds.width - columns * cell_size) / 2.0).max(0.0) + margin,
+            ((bounds.height - rows * cell_size) / 2.0).max(0.0) + margin,
+        ) + self.offset;
+        (cell_size, origin)
+    }
+
+    // Limits a pan so at least one cell of the board stays inside the canvas.
+    fn clamp_offset(&self, offset: Vector, bounds: Size) -> Vector {
+        let (cell_size, origin) = self.layout(bounds);
+        let resting = origin - self.offset;
+        let width = self.board.width() as f32 * cell_size;
+        let height = self.board.height() as f32 * cell_size;
+
+        Vector::new(
+            offset
+                .x
+                .max(cell_size - width - resting.x)
+                .min(bounds.width - cell_size - resting.x),
+            offset
+                .y
+                .max(cell_size - height - resting.y)
+                .min(bounds.height - cell_size - resting.y),
+        )
+    }
+
+    // Space above and to the left of the grid for the row and column labels.
+    fn margin(&self, cell_size: f32) -> f32 {
+        if self.show_labels {
+            cell_size * LABEL_MARGIN
+        } else {
+            0.0
+        }
     }
 }
 
 impl<'a> canvas::Program<UIMessage> for UIGrid {
     fn update(&mut self, event: Event, bounds: Rectangle, cursor: Cursor) -> Option<UIMessage> {
-        let cursor_position = cursor.position_in(&bounds)?;
-        let cell = self.project(cursor_position, bounds.size());
-        let col = cell.x as usize;
-        let row = cell.y as usize;
-
         match event {
-            Event::Mouse(mouse_event) => match mouse_event {
-                mouse::Event::ButtonPressed(button) => match button {
-                    mouse::Button::Left => Some(UIMessage::Reveal(row, col)),
-                    mouse::Button::Right => Some(UIMessage::Flag(row, col)),
+            Event::Mouse(mouse::Event::ButtonPressed(button)) => {
+                let cursor_position = cursor.position_in(&bounds)?;
+                let cell = self.project(cursor_position, bounds.size());
+                if cell.x < 0.0 || cell.y < 0.0 {
+                    return None;
+                }
+                let col = cell.x as usize;
+                let row = cell.y as usize;
+
+                match button {
+                    mouse::Button::Left if self.editing => Some(UIMessage::ToggleMine(row, col)),
+                    mouse::Button::Left => {
+                        let now = Instant::now();
+                        if is_double_click(self.last_click, now, (row, col)) {
+                            self.last_click = None;
+                            Some(UIMessage::Chord(row, col))
+                        } else {
+                            self.last_click = Some((now, (row, col)));
+                            Some(UIMessage::Reveal(row, col))
+                        }
+                    }
+                    mouse::Button::Right if self.shift_held => Some(UIMessage::AutoFlag(row, col)),
+                    mouse::Button::Right => {
+                        self.drag = Some(self.grid_position(cursor_position, bounds.size()));
+                        Some(UIMessage::Flag(row, col))
+                    }
+                    mouse::Button::Middle => {
+                        self.middle_drag = Some((cursor_position, false));
+                        Some(UIMessage::Preview(row, col))
+                    }
                     _ => None,
-                },
+                }
+            }
+            Event::Mouse(mouse::Event::WheelScrolled { delta }) => {
+                cursor.position_in(&bounds)?;
+                let lines = match delta {
+                    mouse::ScrollDelta::Lines { y, .. } => y,
+                    mouse::ScrollDelta::Pixels { y, .. } => y / CELL_SIZE,
+                };
+                Some(UIMessage::Zoom(lines))
+            }
+            Event::Mouse(mouse::Event::CursorMoved { .. }) if self.drag.is_some() => {
+                let start = self.drag?;
+                let end = self.grid_position(cursor.position_in(&bounds)?, bounds.size());
+                let (cell_size, _) = self.layout(bounds.size());
+                let cells = drag_cells(start, end, cell_size);
+                self.drag = Some(end);
+                if cells.is_empty() {
+                    None
+                } else {
+                    Some(UIMessage::FlagCells(cells))
+                }
+            }
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Right)) => {
+                self.drag = None;
+                None
+            }
+            Event::Mouse(mouse::Event::CursorMoved { .. }) if self.middle_drag.is_some() => {
+                let (last, panning) = self.middle_drag?;
+                let position = cursor.position_in(&bounds)?;
+                // Small wobbles while clicking shouldn't turn a chord into a pan.
+                if !panning && position.distance(last) < PAN_THRESHOLD {
+                    return None;
+                }
+
+                self.middle_drag = Some((position, true));
+                let offset = self.clamp_offset(self.offset + (position - last), bounds.size());
+                Some(UIMessage::Pan(offset))
+            }
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Middle)) => {
+                let (_, panning) = self.middle_drag.take()?;
+                if panning {
+                    return None;
+                }
+                let (row, col) = self.preview?;
+                Some(UIMessage::Chord(row, col))
+            }
+            Event::Keyboard(keyboard::Event::KeyPressed { key_code, .. }) if is_shift(key_code) => {
+                self.shift_held = true;
+                None
+            }
+            Event::Keyboard(keyboard::Event::KeyReleased { key_code, .. })
+                if is_shift(key_code) =>
+            {
+                self.shift_held = false;
+                None
+            }
+            Event::Keyboard(_) if !self.keyboard => None,
+            Event::Keyboard(keyboard::Event::KeyPressed {
+                key_code: keyboard::KeyCode::Z,
+                modifiers,
+            }) if modifiers.control => Some(UIMessage::Undo),
+            Event::Keyboard(keyboard::Event::KeyPressed { key_code, .. }) => match key_code {
+                keyboard::KeyCode::R => Some(UIMessage::NewGame),
+                keyboard::KeyCode::S => Some(UIMessage::Save),
+                keyboard::KeyCode::L => Some(UIMessage::Load),
+                keyboard::KeyCode::A => Some(UIMessage::SolveStep),
+                keyboard::KeyCode::H => Some(UIMessage::Hint),
+                keyboard::KeyCode::O => Some(UIMessage::AutoOpen),
+                keyboard::KeyCode::G => Some(UIMessage::RevealSafe),
+                keyboard::KeyCode::E => Some(UIMessage::ToggleEdit),
+                keyboard::KeyCode::P if self.cheats && self.shift_held => Some(UIMessage::Peek),
+                keyboard::KeyCode::Space => Some(UIMessage::Reveal(self.cursor.0, self.cursor.1)),
+                keyboard::KeyCode::F => Some(UIMessage::Flag(self.cursor.0, self.cursor.1)),
+                keyboard::KeyCode::Up
+                | keyboard::KeyCode::Down
+                | keyboard::KeyCode::Left
+                | keyboard::KeyCode::Right => {
+                    let (row, col) = step_cursor(
+                        self.cursor,
+                        key_code,
+                        self.board.width(),
+                        self.board.height(),
+                    );
+                    Some(UIMessage::MoveCursor(row, col))
+                }
                 _ => None,
             },
             _ => None,
         }
     }
 
-    fn draw(&self, bounds: Rectangle, _cursor: Cursor) -> Vec<Geometry> {
-        let cell_width = bounds.width / self.board.width() as f32;
-        let cell_height = bounds.height / self.board.height() as f32;
-        let color_border = Color::from_rgb8(70, 74, 83);
-
-        let grid = self.grid_cache.draw(bounds.size(), |frame| {
-            for row in 0..self.board.width() {
-                for col in 0..self.board.height() {
-                    let position_x = col as f32 * cell_width;
-                    let position_y = row as f32 * cell_height;
-
-                    let color = match self.board.get_cell_state(row, col) {
-                        None => panic!("out of bounds"),
-                        Some(CellState::Mine(true, false)) => Color::from_rgb8(255, 0, 0),
-                        Some(CellState::Mine(false, true)) => Color::from_rgb8(255, 255, 122),
-                        Some(CellState::Neighbours(true, value)) => {
-                            if value != 0 {
-                                let text = Text {
-                                    color: Color::WHITE,
-                                    size: 32.0,
-                                    position: Point::new(position_x + 25.0, position_y + 30.0),
-                                    horizontal_alignment: HorizontalAlignment::Right,
-                                    vertical_alignment: VerticalAlignment::Bottom,
-                                    ..Text::default()
-                                };
-                                frame.fill_text(Text {
-                                    content: format!("{}", value),
-                                    ..text
+    fn draw(&self, bounds: Rectangle, cursor: Cursor) -> Vec<Geometry> {
+        let (cell_size, origin) = self.layout(bounds.size());
+        let cell_width = cell_size;
+        let cell_height = cell_size;
+        let colors = &self.colors;
+        let margin = self.margin(cell_size);
+        let pressed = pressed_cells(&self.board, self.preview);
+
+        let width = self.board.width();
+        let mut geometry: Vec<Geometry> = self
+            .band_caches
+            .iter()
+            .enumerate()
+            .map(|(band, cache)| {
+                let rows = band * BAND_ROWS..((band + 1) * BAND_ROWS).min(self.board.height());
+                cache.draw(bounds.size(), |frame| {
+                    if self.show_labels {
+                        let label = canvas::Text {
+                            color: Color::WHITE,
+                            size: margin * 0.6,
+                            horizontal_alignment: HorizontalAlignment::Center,
+                            vertical_alignment: VerticalAlignment::Center,
+                            ..canvas::Text::default()
+                        };
+                        if band == 0 {
+                            for col in 0..self.board.width() {
+                                frame.fill_text(canvas::Text {
+                                    content: col.to_string(),
+                                    position: Point::new(
+                                        origin.x + (col as f32 + 0.5) * cell_width,
+                                        origin.y - margin / 2.0,
+                                    ),
+                                    ..label
                                 });
                             }
-                            Color::from_rgb8(0, 0, 200)
                         }
-                        _ => Color::from_rgb8(0, 200, 0),
-                    };
-                    frame.fill_rectangle(
-                        Point::new(col as f32 * cell_width, row as f32 * cell_height),
-                        Size::new(cell_width, cell_height),
-                        color_border,
-                    );
-                    frame.fill_rectangle(
-                        Point::new(
-                            (col as f32 * cell_width) + 2.0,
-                            (row as f32 * cell_height) + 2.0,
-                        ),
-                        Size::new(cell_width - 2.0, cell_height - 2.0),
-                        color,
-                    );
+                        for row in rows.clone() {
+                            frame.fill_text(canvas::Text {
+                                content: row.to_string(),
+                                position: Point::new(
+                                    origin.x - margin / 2.0,
+                                    origin.y + (row as f32 + 0.5) * cell_height,
+                                ),
+                                ..label
+                            });
+                        }
+                    }
+
+                    for (row, col, state) in self
+                        .board
+                        .iter_cells()
+                        .skip(rows.start * width)
+                        .take(rows.len() * width)
+                    {
+                        let position_x = origin.x + col as f32 * cell_width;
+                        let position_y = origin.y + row as f32 * cell_height;
+
+                        let state = if self.pending.contains(&(row, col)) {
+                            CellState::Neighbours(false, Marker::None, 0)
+                        } else if self.editing {
+                            // Show the whole layout being designed.
+                            match state {
+                                CellState::Mine(_, marker) => CellState::Mine(true, marker),
+                                CellState::Neighbours(_, marker, count) => {
+                                    CellState::Neighbours(true, marker, count)
+                                }
+                            }
+                        } else {
+                            state
+                        };
+                        let color = match state {
+                            _ if self.hint == Some((row, col)) => colors.hint,
+                            _ if pressed.contains(&(row, col)) => colors.pressed,
+                            CellState::Neighbours(false, Marker::Flag, _)
+                                if self.board.is_lost() =>
+                            {
+                                colors.wrong_flag
+                            }
+                            CellState::Mine(_, Marker::Flag)
+                            | CellState::Neighbours(false, Marker::Flag, _) => colors.flagged,
+                            CellState::Mine(true, _) => match self.explosion_frame {
+                                Some((e_row, e_col, frame)) if (e_row, e_col) == (row, col) => {
+                                    explosion_color(frame, colors.mine)
+                                }
+                                _ => colors.mine,
+                            },
+                            CellState::Mine(false, Marker::Question)
+                            | CellState::Neighbours(false, Marker::Question, _) => colors.question,
+                            CellState::Neighbours(true, _, value) => {
+                                if value != 0 {
+                                    let text_color = number_color(value);
+                                    let text = canvas::Text {
+                                        color: if self.board.is_number_satisfied(row, col) {
+                                            Color {
+                                                a: 0.35,
+                                                ..text_color
+                                            }
+                                        } else {
+                                            text_color
+                                        },
+                                        size: cell_height * 0.8,
+                                        position: cell_center(
+                                            Point::new(position_x, position_y),
+                                            Size::new(cell_width, cell_height),
+                                        ),
+                                        horizontal_alignment: HorizontalAlignment::Center,
+                                        vertical_alignment: VerticalAlignment::Center,
+                                        ..canvas::Text::default()
+                                    };
+                                    frame.fill_text(canvas::Text {
+                                        content: format!("{}", value),
+                                        ..text
+                                    });
+                                }
+                                colors.revealed
+                            }
+                            _ => colors.unrevealed,
+                        };
+                        if self.style.flat {
+                            frame.fill_rectangle(
+                                Point::new(position_x, position_y),
+                                Size::new(cell_width, cell_height),
+                                color,
+                            );
+                        } else {
+                            frame.fill_rectangle(
+                                Point::new(position_x, position_y),
+                                Size::new(cell_width, cell_height),
+                                self.style.border_color,
+                            );
+                            let (inner_position, inner_size) = self.style.inner_cell(
+                                Point::new(position_x, position_y),
+                                Size::new(cell_width, cell_height),
+                            );
+                            frame.fill_rectangle(inner_position, inner_size, color);
+                        }
+
+                        if let Some(glyph) = self.glyphs.as_ref().and_then(|g| g.for_cell(state)) {
+                            frame.fill_text(canvas::Text {
+                                content: glyph.to_string(),
+                                color: Color::BLACK,
+                                size: cell_height * 0.7,
+                                position: cell_center(
+                                    Point::new(position_x, position_y),
+                                    Size::new(cell_width, cell_height),
+                                ),
+                                horizontal_alignment: HorizontalAlignment::Center,
+                                vertical_alignment: VerticalAlignment::Center,
+                                ..canvas::Text::default()
+                            });
+                            continue;
+                        }
+
+                        if colors.cross_mines {
+                            if let CellState::Mine(true, Marker::None) = state {
+                                draw_cross(
+                                    frame,
+                                    Point::new(position_x, position_y),
+                                    Size::new(cell_width, cell_height),
+                                );
+                            }
+                        }
+
+                        match state {
+                            CellState::Mine(_, marker)
+                            | CellState::Neighbours(false, marker, _) => draw_marker(
+                                frame,
+                                marker,
+                                Point::new(position_x, position_y),
+                                Size::new(cell_width, cell_height),
+                            ),
+                            _ => {}
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        if self.style.flat {
+            geometry.push(self.lines_cache.draw(bounds.size(), |frame| {
+                let stroke = Stroke {
+                    color: self.style.border_color,
+                    width: self.style.border_thickness,
+                    ..Stroke::default()
+                };
+                let segments =
+                    gridline_segments(origin, cell_size, self.board.width(), self.board.height());
+
+                for (from, to) in segments {
+                    frame.stroke(&Path::line(from, to), stroke);
                 }
+            }));
+        }
+
+        let mut overlay = Frame::new(bounds.size());
+
+        if self.heatmap && self.board.state() == GameState::Playing {
+            for ((row, col), probability) in self.board.frontier_probabilities() {
+                overlay.fill_rectangle(
+                    Point::new(
+                        origin.x + col as f32 * cell_width,
+                        origin.y + row as f32 * cell_height,
+                    ),
+                    Size::new(cell_width, cell_height),
+                    Color::from_rgba(probability, 1.0 - probability, 0.0, 0.4),
+                );
             }
-        });
-        vec![grid]
+        }
+
+        let now = Instant::now();
+        for &((row, col), start) in &self.fading {
+            let opacity = fade_opacity(now.saturating_duration_since(start));
+            overlay.fill_rectangle(
+                Point::new(
+                    origin.x + col as f32 * cell_width,
+                    origin.y + row as f32 * cell_height,
+                ),
+                Size::new(cell_width, cell_height),
+                Color {
+                    a: 1.0 - opacity,
+                    ..colors.unrevealed
+                },
+            );
+        }
+
+        let (cursor_row, cursor_col) = self.cursor;
+        overlay.stroke(
+            &Path::rectangle(
+                Point::new(
+                    origin.x + cursor_col as f32 * cell_width,
+                    origin.y + cursor_row as f32 * cell_height,
+                ),
+                Size::new(cell_width, cell_height),
+            ),
+            Stroke {
+                color: Color::WHITE,
+                width: 3.0,
+                ..Stroke::default()
+            },
+        );
+
+        if let Some(position) = cursor.position_in(&bounds) {
+            if let Some(content) = self.tooltip(position, bounds.size()) {
+                let size = cell_height * 0.5;
+                let top_left = position + Vector::new(12.0, 12.0);
+                overlay.fill_rectangle(
+                    top_left,
+                    Size::new(size * (content.len() as f32 * 0.6 + 0.8), size * 1.6),
+                    Color::from_rgba8(20, 20, 20, 0.85),
+                );
+                overlay.fill_text(canvas::Text {
+                    content,
+                    color: Color::WHITE,
+                    size,
+                    position: top_left + Vector::new(size * 0.4, size * 0.3),
+                    ..canvas::Text::default()
+                });
+            }
+        }
+
+        if self.paused {
+            overlay.fill_rectangle(
+                Point::ORIGIN,
+                bounds.size(),
+                Color::from_rgba8(20, 20, 20, 0.95),
+            );
+            overlay.fill_text(canvas::Text {
+                content: String::from("Paused"),
+                color: Color::WHITE,
+                size: cell_height,
+                position: Point::new(bounds.width / 2.0, bounds.height / 2.0),
+                horizontal_alignment: HorizontalAlignment::Center,
+                vertical_alignment: VerticalAlignment::Center,
+                ..canvas::Text::default()
+            });
+        }
+
+        geometry.push(overlay.into_geometry());
+        geometry
     }
 
     fn mouse_interaction(&self, bounds: Rectangle, cursor: Cursor) -> mouse::Interaction {
@@ -182,235 +1563,931 @@ impl<'a> canvas::Program<UIMessage> for UIGrid {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
-enum CellState {
-    Mine(bool, bool),
-    Neighbours(bool, u8),
-}
-
-const ALL_DIRECTIONS: [Direction; 8] = [
-    Direction::N,
-    Direction::E,
-    Direction::S,
-    Direction::W,
-    Direction::NE,
-    Direction::SE,
-    Direction::SW,
-    Direction::NW,
-];
-
-enum Direction {
-    N,
-    E,
-    S,
-    W,
-    NE,
-    SE,
-    SW,
-    NW,
-}
-
-impl Direction {
-    fn offset(&self, (row, col): (usize, usize)) -> Option<(usize, usize)> {
-        match self {
-            Self::N if row > 0 => Some((row - 1, col)),
-            Self::E => Some((row, col + 1)),
-            Self::S => Some((row + 1, col)),
-            Self::W if col > 0 => Some((row, col - 1)),
-            Self::NE if row > 0 => Some((row - 1, col + 1)),
-            Self::SE => Some((row + 1, col + 1)),
-            Self::SW if col > 0 => Some((row + 1, col - 1)),
-            Self::NW if row > 0 && col > 0 => Some((row - 1, col - 1)),
-            _ => None,
-        }
-    }
+// The clock stops while paused and as soon as any board is won or lost.
+fn clock_running(started: bool, paused: bool, mut states: impl Iterator<Item = GameState>) -> bool {
+    started && !paused && states.all(|state| state == GameState::Playing)
 }
 
-impl fmt::Display for CellState {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Self::Mine(true, false) => write!(f, "X"),
-            Self::Mine(false, true) => write!(f, "!"),
-            Self::Neighbours(true, count) => write!(f, "{}", count),
-            _ => write!(f, " "),
+// Whether a click on `cell` at `now` completes a double click with the previous one.
+fn is_double_click(
+    previous: Option<(Instant, (usize, usize))>,
+    now: Instant,
+    cell: (usize, usize),
+) -> bool {
+    match previous {
+        Some((time, previous_cell)) => {
+            previous_cell == cell && now.duration_since(time) <= DOUBLE_CLICK_TIME
         }
+        None => false,
     }
 }
 
-struct Board {
-    grid: Vec<Vec<CellState>>,
-    game_over: bool,
+fn window_title(state: GameState, elapsed_seconds: u64, seed: u64) -> String {
+    match state {
+        GameState::Playing => format!("Minesweeper (seed {})", seed),
+        GameState::Won => format!("Minesweeper - won in {}s (seed {})", elapsed_seconds, seed),
+        GameState::Lost => format!(
+            "Minesweeper - lost after {}s (seed {})",
+            elapsed_seconds, seed
+        ),
+    }
 }
 
-impl fmt::Display for Board {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for _ in 0..self.width() {
-            write!(f, "----")?;
-        }
-        write!(f, "-\n")?;
+// Messages that change a board, which are dropped while the game is paused.
+fn is_move(message: &UIMessage) -> bool {
+    matches!(
+        message,
+        UIMessage::Reveal(..)
+            | UIMessage::Flag(..)
+            | UIMessage::FlagCells(..)
+            | UIMessage::ToggleMine(..)
+            | UIMessage::Chord(..)
+            | UIMessage::AutoFlag(..)
+            | UIMessage::Preview(..)
+            | UIMessage::MoveCursor(..)
+            | UIMessage::Peek
+            | UIMessage::Undo
+            | UIMessage::SolveStep
+            | UIMessage::Hint
+            | UIMessage::AutoOpen
+            | UIMessage::RevealSafe
+    )
+}
 
-        for row in &self.grid {
-            for cell in row {
-                write!(f, "| {} ", cell)?;
-            }
-            write!(f, "|\n-")?;
-            for _ in row {
-                write!(f, "----")?;
-            }
-            write!(f, "\n")?;
-        }
-        Ok(())
+// The banner alternates between orange and yellow until it settles on orange.
+fn winner_color(frame: u8) -> Color {
+    if frame < WINNER_FLASH_FRAMES && (frame / WINNER_FLASH_PERIOD) % 2 == 1 {
+        Color::from_rgb8(255, 215, 0)
+    } else {
+        Color::from_rgb8(255, 140, 0)
     }
 }
 
-fn select_random_coords(coords: &mut [(usize, usize)], number: usize) -> &[(usize, usize)] {
-    use rand::seq::SliceRandom;
+// Help a player can ask for, after which a win doesn't count towards the best times.
+fn is_assist(message: &UIMessage) -> bool {
+    matches!(
+        message,
+        UIMessage::Peek
+            | UIMessage::Undo
+            | UIMessage::SolveStep
+            | UIMessage::Hint
+            | UIMessage::AutoOpen
+            | UIMessage::RevealSafe
+    )
+}
 
-    let mut rng = thread_rng();
-    coords.partial_shuffle(&mut rng, number).0
+// Every board's canvas hears every key, so the keys that act on the whole game are picked out of
+// the window's events instead, where they're seen once.
+fn game_key(event: iced_native::Event, cheats: bool) -> Option<UIMessage> {
+    match event {
+        iced_native::Event::Keyboard(keyboard::Event::KeyPressed {
+            key_code: keyboard::KeyCode::P,
+            modifiers,
+        }) if !(cheats && modifiers.shift) => Some(UIMessage::TogglePause),
+        iced_native::Event::Keyboard(keyboard::Event::KeyPressed {
+            key_code: keyboard::KeyCode::D,
+            ..
+        }) => Some(UIMessage::CycleDifficulty),
+        _ => None,
+    }
 }
 
-impl Board {
-    pub fn new(width: usize, height: usize, mines: usize) -> Self {
-        assert!(width > 0);
-        assert!(height > 0);
+// The window events worth sending to `update`, which are only the keys `game_key` looks at.
+fn game_key_event(event: iced_native::Event) -> Option<UIMessage> {
+    let wanted = matches!(
+        event,
+        iced_native::Event::Keyboard(keyboard::Event::KeyPressed {
+            key_code: keyboard::KeyCode::P,
+            ..
+        }) | iced_native::Event::Keyboard(keyboard::Event::KeyPressed {
+            key_code: keyboard::KeyCode::D,
+            ..
+        })
+    );
+    if wanted {
+        Some(UIMessage::Event(event))
+    } else {
+        None
+    }
+}
 
-        let mut grid = Vec::with_capacity(height);
-        let mut coords = Vec::with_capacity(height * width);
+fn is_shift(key_code: keyboard::KeyCode) -> bool {
+    matches!(
+        key_code,
+        keyboard::KeyCode::LShift | keyboard::KeyCode::RShift
+    )
+}
 
-        for row in 0..height {
-            grid.push(Vec::with_capacity(width));
-            for col in 0..width {
-                grid[row].push(CellState::Neighbours(false, 0));
-                coords.push((col, row));
-            }
-        }
+fn step_cursor(
+    (row, col): (usize, usize),
+    key_code: keyboard::KeyCode,
+    width: usize,
+    height: usize,
+) -> (usize, usize) {
+    match key_code {
+        keyboard::KeyCode::Up => (row.saturating_sub(1), col),
+        keyboard::KeyCode::Down => ((row + 1).min(height - 1), col),
+        keyboard::KeyCode::Left => (row, col.saturating_sub(1)),
+        keyboard::KeyCode::Right => (row, (col + 1).min(width - 1)),
+        _ => (row, col),
+    }
+}
 
-        let coords = select_random_coords(&mut coords, mines);
+// The cells a drag from `start` to `end` enters, in order, not counting the one it started in.
+// Points are relative to the grid's top left; cells above or left of the grid are skipped.
+fn drag_cells(start: Point, end: Point, cell_size: f32) -> Vec<(usize, usize)> {
+    let cell_at = |point: Point| {
+        if point.x < 0.0 || point.y < 0.0 {
+            None
+        } else {
+            Some((
+                (point.y / cell_size) as usize,
+                (point.x / cell_size) as usize,
+            ))
+        }
+    };
 
-        for i in 0..mines {
-            let (col, row) = coords[i];
-            grid[row][col] = CellState::Mine(false, false);
+    // Sampling a few times per cell is enough not to skip any along the way.
+    let distance = ((end.x - start.x).powi(2) + (end.y - start.y).powi(2)).sqrt();
+    let steps = ((distance / (cell_size / 4.0)).ceil() as usize).max(1);
 
-            for direction in &ALL_DIRECTIONS {
-                if let Some(offset) = direction.offset((row, col)) {
-                    if in_bounds(width, height, offset) {
-                        let (o_row, o_col) = offset;
-                        let cell = grid[o_row].get_mut(o_col);
-                        match cell {
-                            Some(CellState::Neighbours(_, ref mut value)) => {
-                                *value += 1;
-                            }
-                            _ => {}
-                        }
-                    }
+    let mut last = cell_at(start);
+    let mut cells = Vec::new();
+    for step in 1..=steps {
+        let t = step as f32 / steps as f32;
+        let cell = cell_at(Point::new(
+            start.x + (end.x - start.x) * t,
+            start.y + (end.y - start.y) * t,
+        ));
+        if cell != last {
+            if let Some(cell) = cell {
+                if !cells.contains(&cell) {
+                    cells.push(cell);
                 }
             }
+            last = cell;
         }
+    }
 
-        Self {
-            grid,
-            game_over: false,
-        }
+    cells
+}
+
+// The hidden, unmarked cells around a chord being previewed, which are drawn pressed in.
+fn pressed_cells(board: &Board, preview: Option<(usize, usize)>) -> Vec<(usize, usize)> {
+    match preview {
+        Some((row, col)) if board.state() == GameState::Playing => board
+            .neighbours(row, col)
+            .filter(|&(o_row, o_col)| {
+                matches!(
+                    board.cell(o_row, o_col),
+                    CellState::Mine(false, Marker::None)
+                        | CellState::Neighbours(false, Marker::None, _)
+                )
+            })
+            .collect(),
+        _ => Vec::new(),
     }
+}
 
-    pub fn get_cell_state(&self, row: usize, col: usize) -> Option<CellState> {
-        if !in_bounds(self.width(), self.height(), (row, col)) {
-            return None;
+fn draw_marker(frame: &mut Frame, marker: Marker, top_left: Point, size: Size) {
+    match marker {
+        Marker::Flag => {
+            let [top, tip, bottom] = flag_triangle(top_left, size);
+            frame.fill_rectangle(
+                top,
+                Size::new(size.width * 0.06, size.height * 0.6),
+                Color::BLACK,
+            );
+            frame.fill(
+                &Path::new(|path| {
+                    path.move_to(top);
+                    path.line_to(tip);
+                    path.line_to(bottom);
+                    path.close();
+                }),
+                Color::from_rgb8(220, 0, 0),
+            );
         }
+        Marker::Question => frame.fill_text(canvas::Text {
+            content: String::from("?"),
+            color: Color::from_rgb8(90, 90, 90),
+            size: size.height * 0.8,
+            position: cell_center(top_left, size),
+            horizontal_alignment: HorizontalAlignment::Center,
+            vertical_alignment: VerticalAlignment::Center,
+            ..canvas::Text::default()
+        }),
+        Marker::None => {}
+    }
+}
 
-        Some(self.grid[row][col])
+fn draw_cross(frame: &mut Frame, top_left: Point, size: Size) {
+    let inset = size.width * 0.25;
+    let cross = Path::new(|path| {
+        path.move_to(Point::new(top_left.x + inset, top_left.y + inset));
+        path.line_to(Point::new(
+            top_left.x + size.width - inset,
+            top_left.y + size.height - inset,
+        ));
+        path.move_to(Point::new(
+            top_left.x + size.width - inset,
+            top_left.y + inset,
+        ));
+        path.line_to(Point::new(
+            top_left.x + inset,
+            top_left.y + size.height - inset,
+        ));
+    });
+
+    frame.stroke(
+        &cross,
+        Stroke {
+            color: Color::BLACK,
+            width: 3.0,
+            ..Stroke::default()
+        },
+    );
+}
+
+// The corners of a flag's pennant: the top of the pole, the tip and where it meets the pole again.
+fn flag_triangle(top_left: Point, size: Size) -> [Point; 3] {
+    let pole_x = top_left.x + size.width * 0.3;
+    [
+        Point::new(pole_x, top_left.y + size.height * 0.2),
+        Point::new(
+            top_left.x + size.width * 0.75,
+            top_left.y + size.height * 0.35,
+        ),
+        Point::new(pole_x, top_left.y + size.height * 0.5),
+    ]
+}
+
+// The lines a flat style strokes across the whole grid, one per column edge then one per row edge.
+fn gridline_segments(
+    origin: Point,
+    cell_size: f32,
+    width: usize,
+    height: usize,
+) -> Vec<(Point, Point)> {
+    let right = origin.x + width as f32 * cell_size;
+    let bottom = origin.y + height as f32 * cell_size;
+
+    let columns = (0..=width).map(|col| {
+        let x = origin.x + col as f32 * cell_size;
+        (Point::new(x, origin.y), Point::new(x, bottom))
+    });
+    let rows = (0..=height).map(|row| {
+        let y = origin.y + row as f32 * cell_size;
+        (Point::new(origin.x, y), Point::new(right, y))
+    });
+    columns.chain(rows).collect()
+}
+
+fn cell_center(top_left: Point, size: Size) -> Point {
+    Point::new(
+        top_left.x + size.width / 2.0,
+        top_left.y + size.height / 2.0,
+    )
+}
+
+// Flashes between white and `base` a few times, settling on `base` by the last frame.
+fn explosion_color(frame: u8, base: Color) -> Color {
+    let progress = (frame as f32 / EXPLOSION_FRAMES as f32).min(1.0);
+    let flash = (progress * std::f32::consts::PI * 3.0).sin().abs() * (1.0 - progress);
+
+    Color::from_rgb(
+        base.r + (1.0 - base.r) * flash,
+        base.g + (1.0 - base.g) * flash,
+        base.b + (1.0 - base.b) * flash,
+    )
+}
+
+// How far a revealed cell is from the clicked one, in cells.
+fn reveal_distance(from: (usize, usize), to: (usize, usize)) -> f32 {
+    let rows = from.0 as f32 - to.0 as f32;
+    let cols = from.1 as f32 - to.1 as f32;
+    (rows * rows + cols * cols).sqrt()
+}
+
+// How much of a fading cell shows `elapsed` into its fade, easing out to fully shown.
+fn fade_opacity(elapsed: Duration) -> f32 {
+    let progress = (elapsed.as_secs_f32() / FADE_DURATION.as_secs_f32()).min(1.0);
+    1.0 - (1.0 - progress) * (1.0 - progress)
+}
+
+fn number_color(count: u8) -> Color {
+    match count {
+        1 => Color::from_rgb8(0, 0, 255),
+        2 => Color::from_rgb8(0, 128, 0),
+        3 => Color::from_rgb8(255, 0, 0),
+        4 => Color::from_rgb8(0, 0, 128),
+        5 => Color::from_rgb8(128, 0, 0),
+        6 => Color::from_rgb8(0, 128, 128),
+        7 => Color::BLACK,
+        _ => Color::from_rgb8(128, 128, 128),
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(width: usize, height: usize) -> UIGrid {
+        UIGrid::new(Board::new_seeded(width, height, 1, 0), &Options::default())
+    }
+
+    #[test]
+    fn number_colors() {
+        assert_eq!(number_color(1), Color::from_rgb8(0, 0, 255));
+        assert_eq!(number_color(2), Color::from_rgb8(0, 128, 0));
+        assert_eq!(number_color(3), Color::from_rgb8(255, 0, 0));
+        assert_eq!(number_color(7), Color::BLACK);
+        assert_eq!(number_color(8), Color::from_rgb8(128, 128, 128));
 
-    pub fn flag_cell(&mut self, row: usize, col: usize) {
-        if !in_bounds(self.width(), self.height(), (row, col)) {
-            println!("not in bounds");
-            return;
+        let colors: Vec<Color> = (1..=8).map(number_color).collect();
+        for (i, color) in colors.iter().enumerate() {
+            assert!(!colors[i + 1..].contains(color));
         }
+    }
 
-        let cell = &mut self.grid[row][col];
+    #[test]
+    fn clock_runs_only_while_started_and_playing() {
+        let playing = [GameState::Playing, GameState::Playing];
+        assert!(!clock_running(false, false, playing.iter().copied()));
+        assert!(clock_running(true, false, playing.iter().copied()));
+        assert!(!clock_running(true, true, playing.iter().copied()));
 
-        match cell {
-            CellState::Mine(false, flagged) if *flagged == false => {
-                *flagged = true;
-            }
-            CellState::Mine(false, flagged) if *flagged == true => {
-                *flagged = false;
-            }
-            _ => {}
+        let finished = [GameState::Playing, GameState::Won];
+        assert!(!clock_running(true, false, finished.iter().copied()));
+        assert!(!clock_running(
+            true,
+            false,
+            [GameState::Lost].iter().copied()
+        ));
+    }
+
+    #[test]
+    fn battle_boards_share_a_seed_and_keys_reach_one() {
+        let options = Options {
+            battle: true,
+            ..Options::default()
+        };
+        let (mut game, _) = Minesweeper::new(options);
+        for player in 0..2 {
+            game.update(UIMessage::Player(player, Box::new(UIMessage::Reveal(0, 0))));
         }
+        assert!(game.grids[0].board.mines_placed());
+        assert_eq!(game.grids[0].board, game.grids[1].board);
+
+        let bounds = Rectangle::new(Point::ORIGIN, Size::new(300.0, 300.0));
+        let space = Event::Keyboard(keyboard::Event::KeyPressed {
+            key_code: keyboard::KeyCode::Space,
+            modifiers: Default::default(),
+        });
+        let keyed: Vec<bool> = game
+            .grids
+            .iter_mut()
+            .map(|grid| canvas::Program::update(grid, space, bounds, Cursor::Unavailable).is_some())
+            .collect();
+        assert_eq!(keyed, vec![true, false]);
+    }
+
+    #[test]
+    fn cursor_stays_on_the_board() {
+        use keyboard::KeyCode;
+
+        assert_eq!(step_cursor((0, 0), KeyCode::Up, 5, 3), (0, 0));
+        assert_eq!(step_cursor((0, 0), KeyCode::Left, 5, 3), (0, 0));
+        assert_eq!(step_cursor((2, 4), KeyCode::Down, 5, 3), (2, 4));
+        assert_eq!(step_cursor((2, 4), KeyCode::Right, 5, 3), (2, 4));
+        assert_eq!(step_cursor((1, 1), KeyCode::Down, 5, 3), (2, 1));
+        assert_eq!(step_cursor((1, 1), KeyCode::Right, 5, 3), (1, 2));
+        assert_eq!(step_cursor((1, 1), KeyCode::Space, 5, 3), (1, 1));
+    }
+
+    #[test]
+    fn chord_preview_presses_hidden_unmarked_neighbours() {
+        let mut board = Board::with_mines(3, 3, &[(0, 0), (2, 2)]).unwrap();
+        board.reveal_cell(1, 1);
+        board.reveal_cell(1, 2);
+        board.flag_cell(0, 1);
+
+        let mut pressed = pressed_cells(&board, Some((1, 1)));
+        pressed.sort();
+        assert_eq!(
+            pressed,
+            vec![(0, 0), (0, 2), (1, 0), (2, 0), (2, 1), (2, 2)]
+        );
+        assert!(pressed_cells(&board, None).is_empty());
+
+        board.reveal_cell(0, 0);
+        assert!(pressed_cells(&board, Some((1, 1))).is_empty());
     }
 
-    pub fn reveal_cell(&mut self, row: usize, col: usize) {
-        if self.game_over {
-            println!("game over");
-            return;
+    #[test]
+    fn project_maps_points_to_fixed_size_cells() {
+        let grid = grid(9, 6);
+        let bounds = Size::new(9.0 * CELL_SIZE, 6.0 * CELL_SIZE);
+
+        let cell = grid.project(Point::new(45.0, 75.0), bounds);
+        assert_eq!((cell.x as usize, cell.y as usize), (1, 2));
+        let cell = grid.project(Point::new(269.0, 179.0), bounds);
+        assert_eq!((cell.x as usize, cell.y as usize), (8, 5));
+        assert_eq!(grid.project(Point::ORIGIN, bounds), Point::ORIGIN);
+    }
+
+    #[test]
+    fn cell_center_is_the_middle_of_the_cell() {
+        assert_eq!(
+            cell_center(Point::new(60.0, 90.0), Size::new(30.0, 30.0)),
+            Point::new(75.0, 105.0)
+        );
+        assert_eq!(
+            cell_center(Point::new(0.0, 10.0), Size::new(8.0, 12.0)),
+            Point::new(4.0, 16.0)
+        );
+    }
+
+    #[test]
+    fn flag_triangle_sits_on_the_pole_inside_the_cell() {
+        let [top, tip, bottom] = flag_triangle(Point::new(10.0, 20.0), Size::new(40.0, 40.0));
+
+        assert_eq!(top, Point::new(22.0, 28.0));
+        assert_eq!(tip, Point::new(40.0, 34.0));
+        assert_eq!(bottom, Point::new(22.0, 40.0));
+        assert_eq!(top.x, bottom.x);
+        assert!(tip.x > top.x && top.y < tip.y && tip.y < bottom.y);
+    }
+
+    #[test]
+    fn colorblind_scheme_changes_the_mine_colour() {
+        let default = ColorScheme::default();
+        let colorblind = ColorScheme::colorblind();
+
+        assert_ne!(default.mine, colorblind.mine);
+        assert_ne!(colorblind.mine, colorblind.unrevealed);
+        assert!(colorblind.cross_mines && !default.cross_mines);
+    }
+
+    #[test]
+    fn project_follows_the_zoom() {
+        let mut grid = grid(9, 6);
+        grid.zoom = 2.0;
+        let bounds = Size::new(9.0 * CELL_SIZE * 2.0, 6.0 * CELL_SIZE * 2.0);
+
+        let cell = grid.project(Point::new(130.0, 70.0), bounds);
+        assert_eq!((cell.x as usize, cell.y as usize), (2, 1));
+        let cell = grid.project(Point::new(59.0, 59.0), bounds);
+        assert_eq!((cell.x as usize, cell.y as usize), (0, 0));
+
+        grid.zoom = 0.5;
+        let bounds = Size::new(9.0 * CELL_SIZE * 0.5, 6.0 * CELL_SIZE * 0.5);
+        let cell = grid.project(Point::new(16.0, 46.0), bounds);
+        assert_eq!((cell.x as usize, cell.y as usize), (1, 3));
+    }
+
+    #[test]
+    fn explosion_flashes_and_settles_on_the_base_colour() {
+        let base = Color::from_rgb8(255, 0, 0);
+
+        assert_eq!(explosion_color(0, base), base);
+        assert_eq!(explosion_color(EXPLOSION_FRAMES, base), base);
+        let flash = explosion_color(3, base);
+        assert_eq!(flash.r, 1.0);
+        assert!(flash.g > 0.5 && flash.b > 0.5);
+        assert!(explosion_color(EXPLOSION_FRAMES - 1, base).g < flash.g);
+    }
+
+    #[test]
+    fn glyphs_tell_flags_from_question_marks() {
+        let glyphs = Glyphs::default();
+
+        assert_eq!(
+            glyphs.for_cell(CellState::Neighbours(false, Marker::Flag, 2)),
+            Some("F")
+        );
+        assert_eq!(
+            glyphs.for_cell(CellState::Mine(false, Marker::Flag)),
+            Some("F")
+        );
+        assert_eq!(
+            glyphs.for_cell(CellState::Neighbours(false, Marker::Question, 2)),
+            Some("?")
+        );
+        assert_eq!(
+            glyphs.for_cell(CellState::Mine(false, Marker::Question)),
+            Some("?")
+        );
+        assert_eq!(
+            glyphs.for_cell(CellState::Neighbours(false, Marker::None, 2)),
+            None
+        );
+    }
+
+    #[test]
+    fn inner_cell_leaves_room_for_the_top_and_left_border() {
+        let style = GridStyle {
+            border_thickness: 3.0,
+            ..GridStyle::default()
+        };
+
+        assert_eq!(
+            style.inner_cell(Point::new(30.0, 60.0), Size::new(30.0, 30.0)),
+            (Point::new(33.0, 63.0), Size::new(27.0, 27.0))
+        );
+        // A border thicker than the cell is cut down to fit it.
+        assert_eq!(
+            style.inner_cell(Point::new(0.0, 0.0), Size::new(2.0, 4.0)),
+            (Point::new(2.0, 2.0), Size::new(0.0, 2.0))
+        );
+    }
+
+    #[test]
+    fn drag_cells_lists_each_entered_cell_once() {
+        let cells = |start: (f32, f32), end: (f32, f32)| {
+            drag_cells(
+                Point::new(start.0, start.1),
+                Point::new(end.0, end.1),
+                CELL_SIZE,
+            )
+        };
+
+        assert!(cells((5.0, 5.0), (25.0, 25.0)).is_empty());
+        assert_eq!(cells((5.0, 5.0), (95.0, 5.0)), vec![(0, 1), (0, 2), (0, 3)]);
+        assert_eq!(
+            cells((40.0, 95.0), (40.0, 5.0)),
+            vec![(2, 1), (1, 1), (0, 1)]
+        );
+        assert_eq!(cells((-20.0, 5.0), (40.0, 5.0)), vec![(0, 0), (0, 1)]);
+    }
+
+    // Notes down which sounds a game asks for.
+    #[derive(Default)]
+    struct Recorder(Vec<&'static str>);
+
+    impl SoundSink for Recorder {
+        fn on_reveal(&mut self) {
+            self.0.push("reveal");
         }
 
-        if !in_bounds(self.width(), self.height(), (row, col)) {
-            println!("not in bounds");
-            return;
+        fn on_flag(&mut self) {
+            self.0.push("flag");
         }
 
-        let cell = &mut self.grid[row][col];
-        println!("{:?}", cell);
+        fn on_win(&mut self) {
+            self.0.push("win");
+        }
 
-        match cell {
-            CellState::Mine(ref mut revealed, false) if *revealed == false => {
-                println!("Game over!");
-                *revealed = true;
-                self.game_over = true;
-            }
-            CellState::Mine(ref mut revealed, true) if *revealed == false => return,
-            _ => {
-                println!("revealing cells");
-                let mut closed = HashSet::new();
-                self.reveal_cell_dfs(row, col, &mut closed);
-            }
+        fn on_lose(&mut self) {
+            self.0.push("lose");
+        }
+    }
+
+    #[test]
+    fn sounds_follow_the_game() {
+        let layout: &[&[bool]] = &[&[true, false, false], &[false; 3], &[false; 3]];
+        let options = Options::default();
+
+        let mut sound = Recorder::default();
+        let mut won = UIGrid::new(Board::from_grid(layout).unwrap(), &options);
+        won.update(UIMessage::Flag(0, 0), &mut sound);
+        won.update(UIMessage::Reveal(2, 2), &mut sound);
+        won.update(UIMessage::Reveal(1, 1), &mut sound);
+        assert_eq!(sound.0, vec!["flag", "reveal", "win"]);
+
+        let mut sound = Recorder::default();
+        let mut lost = UIGrid::new(Board::from_grid(layout).unwrap(), &options);
+        lost.update(UIMessage::Reveal(0, 0), &mut sound);
+        lost.update(UIMessage::Flag(1, 1), &mut sound);
+        assert_eq!(sound.0, vec!["lose"]);
+    }
+
+    fn key(key_code: keyboard::KeyCode, shift: bool) -> iced_native::Event {
+        iced_native::Event::Keyboard(keyboard::Event::KeyPressed {
+            key_code,
+            modifiers: keyboard::ModifiersState {
+                shift,
+                ..Default::default()
+            },
+        })
+    }
+
+    #[test]
+    fn pause_is_toggled_once_and_stops_play() {
+        let options = Options {
+            battle: true,
+            ..Options::default()
+        };
+        let (mut game, _) = Minesweeper::new(options);
+        let bounds = Rectangle::new(Point::ORIGIN, Size::new(300.0, 300.0));
+        for grid in &mut game.grids {
+            let event = Event::Keyboard(keyboard::Event::KeyPressed {
+                key_code: keyboard::KeyCode::P,
+                modifiers: Default::default(),
+            });
+            assert!(canvas::Program::update(grid, event, bounds, Cursor::Unavailable).is_none());
         }
+
+        game.update(UIMessage::Event(key(keyboard::KeyCode::P, false)));
+        assert!(game.paused);
+        assert!(game.grids.iter().all(|grid| grid.paused));
+
+        game.update(UIMessage::Player(1, Box::new(UIMessage::Reveal(0, 0))));
+        assert_eq!(game.grids[1].board.revealed_count(), 0);
+        assert!(!game.started);
+
+        game.update(UIMessage::Event(key(keyboard::KeyCode::P, false)));
+        assert!(!game.paused);
+        game.update(UIMessage::Player(1, Box::new(UIMessage::Reveal(0, 0))));
+        assert!(game.grids[1].board.revealed_count() > 0);
+        assert!(game.started);
+    }
+
+    #[test]
+    fn shift_p_peeks_instead_of_pausing_with_cheats() {
+        assert!(game_key(key(keyboard::KeyCode::P, true), true).is_none());
+        assert!(matches!(
+            game_key(key(keyboard::KeyCode::P, true), false),
+            Some(UIMessage::TogglePause)
+        ));
+        assert!(game_key(key(keyboard::KeyCode::Space, false), false).is_none());
+    }
+
+    #[test]
+    fn a_flag_redraws_only_the_cells_around_it() {
+        let mut grid = grid(100, 100);
+        let (hint, preview) = (grid.hint, grid.preview);
+        assert!(grid.changed_cells(hint, preview).is_empty());
+
+        grid.board.flag_cell(50, 50);
+        let cells = grid.changed_cells(hint, preview);
+        assert_eq!(cells.len(), 9);
+        assert!(cells
+            .iter()
+            .all(|&(row, col)| { (49..=51).contains(&row) && (49..=51).contains(&col) }));
+        assert!(grid.changed_cells(hint, preview).is_empty());
+    }
+
+    #[test]
+    fn fitted_grid_is_centred_and_projects_from_its_edges() {
+        let mut grid = grid(4, 2);
+        grid.fit_window = true;
+        let cell = |grid: &UIGrid, x: f32, y: f32, bounds: Size| {
+            let cell = grid.project(Point::new(x, y), bounds);
+            (cell.x.floor() as isize, cell.y.floor() as isize)
+        };
+
+        // Too wide: 50px cells, with 100px spare either side.
+        let wide = Size::new(400.0, 100.0);
+        assert_eq!(cell(&grid, 101.0, 1.0, wide), (0, 0));
+        assert_eq!(cell(&grid, 299.0, 99.0, wide), (3, 1));
+        assert_eq!(cell(&grid, 99.0, 50.0, wide), (-1, 1));
+        assert_eq!(cell(&grid, 301.0, 50.0, wide), (4, 1));
+
+        // Too tall: 50px cells, with 100px spare above and below.
+        let tall = Size::new(200.0, 300.0);
+        assert_eq!(cell(&grid, 1.0, 101.0, tall), (0, 0));
+        assert_eq!(cell(&grid, 199.0, 199.0, tall), (3, 1));
+        assert_eq!(cell(&grid, 100.0, 99.0, tall), (2, -1));
+        assert_eq!(cell(&grid, 100.0, 201.0, tall), (2, 2));
+    }
+
+    #[test]
+    fn glyphs_map_revealed_mines_and_emoji() {
+        let glyphs = Glyphs::default();
+        assert_eq!(
+            glyphs.for_cell(CellState::Mine(true, Marker::None)),
+            Some("X")
+        );
+        assert_eq!(glyphs.for_cell(CellState::Mine(false, Marker::None)), None);
+        assert_eq!(
+            glyphs.for_cell(CellState::Neighbours(true, Marker::None, 3)),
+            None
+        );
+
+        let emoji = Glyphs::emoji();
+        assert_eq!(
+            emoji.for_cell(CellState::Mine(true, Marker::None)),
+            Some("\u{1f4a3}")
+        );
+        assert_eq!(
+            emoji.for_cell(CellState::Mine(false, Marker::Flag)),
+            Some("\u{1f6a9}")
+        );
+        assert_eq!(
+            emoji.for_cell(CellState::Neighbours(false, Marker::Question, 1)),
+            Some("\u{2753}")
+        );
     }
 
-    fn reveal_cell_dfs(&mut self, row: usize, col: usize, closed: &mut HashSet<(usize, usize)>) {
-        if !in_bounds(self.width(), self.height(), (row, col)) {
-            return;
+    #[test]
+    fn project_follows_the_pan_offset() {
+        let mut grid = grid(9, 6);
+        let bounds = Size::new(9.0 * CELL_SIZE, 6.0 * CELL_SIZE);
+        grid.offset = Vector::new(45.0, -30.0);
+
+        let cell = grid.project(Point::new(45.0, 0.0), bounds);
+        assert_eq!(cell, Point::new(0.0, 1.0));
+        let cell = grid.project(Point::new(80.0, 75.0), bounds);
+        assert_eq!((cell.x as usize, cell.y as usize), (1, 3));
+        let cell = grid.project(Point::new(40.0, 0.0), bounds);
+        assert!(cell.x < 0.0);
+    }
+
+    #[test]
+    fn new_daily_game_keeps_the_daily_seed() {
+        let options = Options {
+            daily: true,
+            ..Options::default()
+        };
+        let (mut game, _) = Minesweeper::new(options);
+        game.update(UIMessage::NewGame);
+
+        let (year, month, day) = today();
+        assert_eq!(game.grids[0].board.seed(), daily_seed(year, month, day));
+    }
+
+    #[test]
+    fn only_game_keys_reach_update() {
+        assert!(game_key_event(key(keyboard::KeyCode::P, false)).is_some());
+        assert!(game_key_event(key(keyboard::KeyCode::D, true)).is_some());
+        assert!(game_key_event(key(keyboard::KeyCode::A, false)).is_none());
+        let moved = mouse::Event::CursorMoved { x: 1.0, y: 2.0 };
+        assert!(game_key_event(iced_native::Event::Mouse(moved)).is_none());
+    }
+
+    #[test]
+    fn forced_guess_is_remembered_until_the_board_changes() {
+        let mut grid = grid(4, 4);
+        assert!(!grid.has_forced_guess());
+
+        grid.update(UIMessage::Reveal(0, 0), &mut Silent);
+        let forced = grid.board.has_forced_guess();
+        assert_eq!(grid.has_forced_guess(), forced);
+        let cells: Vec<CellState> = grid.board.iter_cells().map(|(_, _, cell)| cell).collect();
+        assert_eq!(grid.forced_guess, Some((cells, forced)));
+    }
+
+    #[test]
+    fn the_last_player_standing_wins() {
+        let options = Options {
+            battle: true,
+            ..Options::default()
+        };
+        let (mut game, _) = Minesweeper::new(options);
+        game.update(UIMessage::Player(0, Box::new(UIMessage::Reveal(0, 0))));
+        let (row, col) = game.grids[0].board.mine_positions()[0];
+        game.update(UIMessage::Player(0, Box::new(UIMessage::Reveal(row, col))));
+
+        assert!(game.grids[0].board.is_lost());
+        assert_eq!(game.winner, Some(1));
+    }
+
+    #[test]
+    fn winner_banner_flashes_then_settles() {
+        let orange = Color::from_rgb8(255, 140, 0);
+        let yellow = Color::from_rgb8(255, 215, 0);
+        assert_eq!(winner_color(0), orange);
+        assert_eq!(winner_color(WINNER_FLASH_PERIOD), yellow);
+        assert_eq!(winner_color(WINNER_FLASH_PERIOD * 2), orange);
+        assert_eq!(
+            winner_color(WINNER_FLASH_FRAMES + WINNER_FLASH_PERIOD),
+            orange
+        );
+
+        let options = Options {
+            battle: true,
+            ..Options::default()
+        };
+        let (mut game, _) = Minesweeper::new(options);
+        game.declare_winner(1);
+        for _ in 0..WINNER_FLASH_FRAMES + 5 {
+            game.update(UIMessage::Frame);
         }
+        assert_eq!(game.winner_frame, WINNER_FLASH_FRAMES);
+    }
+
+    #[test]
+    fn assisted_wins_are_not_ranked() {
+        let options = Options {
+            cheats: true,
+            ..Options::default()
+        };
+        let (mut game, _) = Minesweeper::new(options);
+        let scores = game.scores.clone();
+        game.update(UIMessage::Player(0, Box::new(UIMessage::Reveal(0, 0))));
+        assert!(game.ranked);
+
+        game.update(UIMessage::Player(0, Box::new(UIMessage::Peek)));
+        game.update(UIMessage::Player(0, Box::new(UIMessage::Peek)));
+        assert!(!game.ranked);
 
-        if closed.contains(&(row, col)) {
-            // might not need this.
-            return;
+        let board = &game.grids[0].board;
+        let mines = board.mine_positions();
+        let safe: Vec<(usize, usize)> = (0..board.height())
+            .flat_map(|row| (0..board.width()).map(move |col| (row, col)))
+            .filter(|cell| !mines.contains(cell))
+            .collect();
+        for (row, col) in safe {
+            game.update(UIMessage::Player(0, Box::new(UIMessage::Reveal(row, col))));
         }
+        assert!(game.grids[0].board.is_won());
+        assert_eq!(game.scores, scores);
+    }
 
-        closed.insert((row, col));
-        let cell = &mut self.grid[row][col];
+    #[test]
+    fn window_title_follows_the_game() {
+        assert_eq!(
+            window_title(GameState::Playing, 12, 7),
+            "Minesweeper (seed 7)"
+        );
+        assert_eq!(
+            window_title(GameState::Won, 12, 7),
+            "Minesweeper - won in 12s (seed 7)"
+        );
+        assert_eq!(
+            window_title(GameState::Lost, 3, 7),
+            "Minesweeper - lost after 3s (seed 7)"
+        );
+    }
 
-        match cell {
-            CellState::Neighbours(true, _) => {}
-            CellState::Neighbours(ref mut revealed, count) if *count > 0 => {
-                *revealed = true;
-            }
-            CellState::Neighbours(ref mut revealed, 0) => {
-                println!("revealing neighbours: ({}, {})", row, col);
-                *revealed = true;
+    #[test]
+    fn difficulty_cycles_once_per_key_in_order() {
+        let options = Options {
+            battle: true,
+            ..Options::default()
+        };
+        let (mut game, _) = Minesweeper::new(options);
+        assert_eq!(game.options.difficulty, Difficulty::Beginner);
 
-                for direction in &ALL_DIRECTIONS {
-                    if let Some((o_row, o_col)) = direction.offset((row, col)) {
-                        self.reveal_cell_dfs(o_row, o_col, closed);
-                    }
-                }
+        let order = [
+            Difficulty::Intermediate,
+            Difficulty::Expert,
+            Difficulty::Beginner,
+        ];
+        for &difficulty in order.iter() {
+            game.update(UIMessage::Event(key(keyboard::KeyCode::D, false)));
+            assert_eq!(game.options.difficulty, difficulty);
+            for grid in &game.grids {
+                let (width, height, mines) = difficulty.parameters();
+                assert_eq!(
+                    (
+                        grid.board.width(),
+                        grid.board.height(),
+                        grid.board.mine_count()
+                    ),
+                    (width, height, mines)
+                );
             }
-            _ => {}
         }
     }
 
-    pub fn height(&self) -> usize {
-        self.grid.len()
+    #[test]
+    fn flat_style_strokes_one_line_per_grid_edge() {
+        let origin = Point::new(10.0, 20.0);
+        let segments = gridline_segments(origin, CELL_SIZE, 9, 6);
+
+        assert_eq!(segments.len(), (9 + 1) + (6 + 1));
+        assert_eq!(segments[0], (origin, Point::new(10.0, 200.0)));
+        assert_eq!(
+            segments[9],
+            (Point::new(280.0, 20.0), Point::new(280.0, 200.0))
+        );
+        assert_eq!(
+            segments[16],
+            (Point::new(10.0, 200.0), Point::new(280.0, 200.0))
+        );
     }
 
-    pub fn width(&self) -> usize {
-        self.grid.first().unwrap().len()
+    #[test]
+    fn double_clicks_need_the_same_cell_in_time() {
+        let first = Instant::now();
+        let soon = first + DOUBLE_CLICK_TIME / 2;
+        let late = first + DOUBLE_CLICK_TIME + Duration::from_millis(1);
+
+        assert!(!is_double_click(None, first, (1, 1)));
+        assert!(is_double_click(Some((first, (1, 1))), soon, (1, 1)));
+        assert!(is_double_click(
+            Some((first, (1, 1))),
+            first + DOUBLE_CLICK_TIME,
+            (1, 1)
+        ));
+        assert!(!is_double_click(Some((first, (1, 1))), late, (1, 1)));
+        assert!(!is_double_click(Some((first, (1, 1))), soon, (1, 2)));
     }
-}
 
-pub fn in_bounds(width: usize, height: usize, (row, col): (usize, usize)) -> bool {
-    row < height && col < width
+    #[test]
+    fn fades_start_further_out_later_and_ease_in() {
+        assert_eq!(reveal_distance((2, 2), (2, 2)), 0.0);
+        assert_eq!(reveal_distance((2, 2), (2, 5)), 3.0);
+        assert_eq!(reveal_distance((0, 0), (3, 4)), 5.0);
+        assert_eq!(reveal_distance((3, 4), (0, 0)), 5.0);
+
+        assert_eq!(fade_opacity(Duration::from_millis(0)), 0.0);
+        assert_eq!(fade_opacity(FADE_DURATION / 2), 0.75);
+        assert_eq!(fade_opacity(FADE_DURATION), 1.0);
+        assert_eq!(fade_opacity(FADE_DURATION * 2), 1.0);
+        let quarter = fade_opacity(FADE_DURATION / 4);
+        assert!(quarter > 0.25 && quarter < 0.75);
+    }
 }
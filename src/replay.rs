@@ -0,0 +1,110 @@
+use crate::{Board, GameState, Marker};
+use alloc::vec::Vec;
+#[cfg(feature = "serde")]
+use serde_crate::{Deserialize, Serialize};
+
+// A single player move, as recorded by a board with recording turned on.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(crate = "serde_crate"))]
+pub enum Move {
+    Reveal(usize, usize),
+    Flag(usize, usize),
+    Chord(usize, usize),
+}
+
+impl Board {
+    // Starts recording every move applied from now on, see `recorded_moves`.
+    pub fn with_recording(mut self) -> Self {
+        self.recording = Some(Vec::new());
+        self
+    }
+
+    // The moves applied since recording started, oldest first. Empty if the board isn't recording.
+    pub fn recorded_moves(&self) -> &[Move] {
+        self.recording.as_deref().unwrap_or(&[])
+    }
+
+    // Applies `moves` in order to a copy of `initial`. Replaying a board's recorded moves onto the
+    // board it started from, e.g. a fresh `new_seeded` with the same seed, reproduces the game.
+    pub fn replay(moves: &[Move], initial: &Board) -> Board {
+        let mut board = initial.clone();
+
+        for &next in moves {
+            match next {
+                Move::Reveal(row, col) => {
+                    board.reveal_cell(row, col);
+                }
+                Move::Flag(row, col) => {
+                    board.flag_cell(row, col);
+                }
+                Move::Chord(row, col) => board.chord_cell(row, col),
+            }
+        }
+
+        board
+    }
+
+    // Moves made once the game is over can't change anything, so they're left out.
+    pub(crate) fn record_move(&mut self, next: Move) {
+        if self.state != GameState::Playing {
+            return;
+        }
+
+        if let Some(moves) = &mut self.recording {
+            moves.push(next);
+        }
+    }
+
+    // Records a marker set directly, e.g. by the solver, as the flag clicks that cycle the cell's
+    // marker from `from` to `to`.
+    pub(crate) fn record_marker(&mut self, row: usize, col: usize, from: Marker, to: Marker) {
+        let mut marker = from;
+        while marker != to {
+            self.record_move(Move::Flag(row, col));
+            marker = marker.next();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::board;
+
+    #[test]
+    fn replaying_recorded_moves_reproduces_the_game() {
+        let initial = board(&["*...", "....", "..*.", "...."]);
+        let mut played = initial.clone().with_recording();
+
+        played.flag_cell(0, 0);
+        played.reveal_cell(0, 3);
+        played.flag_cell(2, 2);
+        played.chord_cell(1, 1);
+        played.reveal_cell(3, 3);
+        assert_eq!(
+            played.recorded_moves(),
+            &[
+                Move::Flag(0, 0),
+                Move::Reveal(0, 3),
+                Move::Flag(2, 2),
+                Move::Chord(1, 1),
+                Move::Reveal(3, 3),
+            ]
+        );
+
+        let replayed = Board::replay(played.recorded_moves(), &initial);
+        assert_eq!(replayed, played);
+        assert_eq!(replayed.revealed_count(), played.revealed_count());
+    }
+
+    #[test]
+    fn moves_after_the_game_ends_are_not_recorded() {
+        let mut board = board(&["*.", ".."]).with_recording();
+        board.reveal_cell(0, 0);
+        board.reveal_cell(1, 1);
+        board.flag_cell(0, 1);
+
+        assert_eq!(board.recorded_moves(), &[Move::Reveal(0, 0)]);
+    }
+}
@@ -0,0 +1,284 @@
+use crate::{
+    Board, CellState, ChordSafety, Connectivity, FloodStyle, GameState, GenerationOptions, Marker,
+    Topology, WinCondition,
+};
+use alloc::string::String;
+use alloc::{format, vec::Vec};
+use core::fmt;
+
+// Boards are written as `{width}x{height};{mines};{state};{seed};{rows}` where `state` is one of
+// `-` (mines not placed yet), `p`, `w` or `l`, and rows are separated by `/`. Each cell is two
+// characters: `*` or a neighbour count, followed by `.`/`f`/`q` for a hidden cell with no marker,
+// a flag or a question mark, or `o`/`F`/`Q` for the revealed equivalents.
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    BadDimensions,
+    BadHeader,
+    UnknownCellToken(String),
+    GridSizeMismatch,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadDimensions => write!(f, "invalid board dimensions"),
+            Self::BadHeader => write!(f, "invalid board header"),
+            Self::UnknownCellToken(token) => write!(f, "unknown cell token '{}'", token),
+            Self::GridSizeMismatch => write!(f, "grid doesn't match the board dimensions"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+impl Board {
+    pub fn to_string_repr(&self) -> String {
+        let state = match self.state {
+            GameState::Playing if !self.mines_placed => '-',
+            GameState::Playing => 'p',
+            GameState::Won => 'w',
+            GameState::Lost => 'l',
+        };
+
+        let mut repr = format!(
+            "{}x{};{};{};{};",
+            self.width(),
+            self.height(),
+            self.mines,
+            state,
+            self.seed
+        );
+
+        for (row, col, cell) in self.iter_cells() {
+            if row > 0 && col == 0 {
+                repr.push('/');
+            }
+            let (kind, state) = cell_token(&cell);
+            repr.push(kind);
+            repr.push(state);
+        }
+
+        repr
+    }
+
+    pub fn from_string_repr(repr: &str) -> Result<Board, ParseError> {
+        let mut fields = repr.trim().split(';');
+
+        let (width, height) = parse_dimensions(fields.next().unwrap_or_default())?;
+        let mines = fields
+            .next()
+            .and_then(|field| field.parse().ok())
+            .filter(|&mines| mines < width * height)
+            .ok_or(ParseError::BadHeader)?;
+        let (state, mines_placed) = match fields.next() {
+            Some("-") => (GameState::Playing, false),
+            Some("p") => (GameState::Playing, true),
+            Some("w") => (GameState::Won, true),
+            Some("l") => (GameState::Lost, true),
+            _ => return Err(ParseError::BadHeader),
+        };
+        let seed = fields
+            .next()
+            .and_then(|field| field.parse().ok())
+            .ok_or(ParseError::BadHeader)?;
+        let cells = fields.next().ok_or(ParseError::GridSizeMismatch)?;
+
+        if fields.next().is_some() {
+            return Err(ParseError::BadHeader);
+        }
+
+        // Not sized up front: the header's dimensions are only trusted once the rows match them.
+        let mut grid = Vec::new();
+        let mut rows = 0;
+        for row in cells.split('/') {
+            let tokens: Vec<char> = row.chars().collect();
+            if tokens.len() != width * 2 {
+                return Err(ParseError::GridSizeMismatch);
+            }
+
+            let row = tokens
+                .chunks(2)
+                .map(|token| parse_cell(token[0], token[1]))
+                .collect::<Result<Vec<_>, _>>()?;
+            grid.extend(row);
+            rows += 1;
+        }
+
+        if rows != height {
+            return Err(ParseError::GridSizeMismatch);
+        }
+
+        // Once the mines are down the header has to agree with the grid.
+        let placed = grid
+            .iter()
+            .filter(|cell| matches!(cell, CellState::Mine(..)))
+            .count();
+        if mines_placed && placed != mines {
+            return Err(ParseError::BadHeader);
+        }
+
+        Ok(Self {
+            grid,
+            width,
+            height,
+            state,
+            mines,
+            mines_placed,
+            seed,
+            topology: Topology::Flat,
+            connectivity: Connectivity::Eight,
+            win_condition: WinCondition::AllSafeRevealed,
+            flood_style: FloodStyle::IncludeBorder,
+            chord_safety: ChordSafety::Strict,
+            max_flags: None,
+            generation: GenerationOptions::default(),
+            peek_snapshot: None,
+            undo: None,
+            recording: None,
+        })
+    }
+}
+
+fn parse_dimensions(field: &str) -> Result<(usize, usize), ParseError> {
+    let mut parts = field.split('x');
+    let width: usize = parts
+        .next()
+        .and_then(|part| part.parse().ok())
+        .ok_or(ParseError::BadDimensions)?;
+    let height: usize = parts
+        .next()
+        .and_then(|part| part.parse().ok())
+        .ok_or(ParseError::BadDimensions)?;
+
+    if width == 0 || height == 0 || parts.next().is_some() {
+        return Err(ParseError::BadDimensions);
+    }
+    // Sizes whose grid couldn't even be written out, at two characters a cell, are rejected here
+    // so the rest of the parser can multiply them freely.
+    if width
+        .checked_mul(height)
+        .and_then(|cells| cells.checked_mul(2))
+        .is_none()
+    {
+        return Err(ParseError::BadDimensions);
+    }
+
+    Ok((width, height))
+}
+
+fn cell_token(cell: &CellState) -> (char, char) {
+    let (kind, revealed, marker) = match *cell {
+        CellState::Mine(revealed, marker) => ('*', revealed, marker),
+        CellState::Neighbours(revealed, marker, count) => (
+            core::char::from_digit(count as u32, 10).unwrap(),
+            revealed,
+            marker,
+        ),
+    };
+
+    let state = match (revealed, marker) {
+        (false, Marker::None) => '.',
+        (false, Marker::Flag) => 'f',
+        (false, Marker::Question) => 'q',
+        (true, Marker::None) => 'o',
+        (true, Marker::Flag) => 'F',
+        (true, Marker::Question) => 'Q',
+    };
+
+    (kind, state)
+}
+
+fn parse_cell(kind: char, state: char) -> Result<CellState, ParseError> {
+    let unknown = || ParseError::UnknownCellToken(format!("{}{}", kind, state));
+
+    let (revealed, marker) = match state {
+        '.' => (false, Marker::None),
+        'f' => (false, Marker::Flag),
+        'q' => (false, Marker::Question),
+        'o' => (true, Marker::None),
+        'F' => (true, Marker::Flag),
+        'Q' => (true, Marker::Question),
+        _ => return Err(unknown()),
+    };
+
+    match kind {
+        '*' => Ok(CellState::Mine(revealed, marker)),
+        '0'..='8' => Ok(CellState::Neighbours(
+            revealed,
+            marker,
+            kind.to_digit(10).unwrap() as u8,
+        )),
+        _ => Err(unknown()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::board;
+
+    #[test]
+    fn round_trips_every_cell() {
+        let mut board = board(&["*...", "..*.", "...."]);
+        board.reveal_cell(2, 0);
+        board.flag_cell(0, 0);
+        board.flag_cell(1, 3);
+        board.flag_cell(1, 3);
+
+        let parsed = Board::from_string_repr(&board.to_string_repr()).unwrap();
+        assert_eq!(parsed.width(), board.width());
+        assert_eq!(parsed.height(), board.height());
+        assert_eq!(parsed.mine_count(), board.mine_count());
+        assert_eq!(parsed.state(), board.state());
+        for row in 0..board.height() {
+            for col in 0..board.width() {
+                assert_eq!(
+                    parsed.get_cell_state(row, col),
+                    board.get_cell_state(row, col)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_a_mine_count_that_does_not_fit() {
+        assert_eq!(
+            Board::from_string_repr("1x2;5;p;0;0./0."),
+            Err(ParseError::BadHeader)
+        );
+        assert_eq!(
+            Board::from_string_repr("1x2;2;-;0;0./0."),
+            Err(ParseError::BadHeader)
+        );
+    }
+
+    #[test]
+    fn rejects_a_mine_count_that_disagrees_with_the_grid() {
+        assert_eq!(
+            Board::from_string_repr("2x1;0;p;0;*.1."),
+            Err(ParseError::BadHeader)
+        );
+        assert!(Board::from_string_repr("2x1;1;p;0;*.1.").is_ok());
+        // Before the first reveal the grid is blank and the header alone says how many to place.
+        assert!(Board::from_string_repr("2x1;1;-;0;0.0.").is_ok());
+    }
+
+    #[test]
+    fn rejects_dimensions_too_large_to_hold() {
+        assert_eq!(
+            Board::from_string_repr("4000000000x4000000000;0;-;0;.."),
+            Err(ParseError::BadDimensions)
+        );
+        let half = usize::MAX / 2 + 1;
+        assert_eq!(
+            Board::from_string_repr(&format!("{}x1;0;-;0;0.", half)),
+            Err(ParseError::BadDimensions)
+        );
+        assert_eq!(
+            Board::from_string_repr("3x1;0;-;0;0."),
+            Err(ParseError::GridSizeMismatch)
+        );
+    }
+}
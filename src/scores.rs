@@ -0,0 +1,76 @@
+use crate::Difficulty;
+use serde_crate::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter};
+use std::path::Path;
+
+// The fastest winning time, in seconds, for each difficulty.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "serde_crate")]
+pub struct Scores {
+    best: HashMap<Difficulty, u64>,
+}
+
+impl Scores {
+    // Returns whether `seconds` is a new best for `difficulty`.
+    pub fn record(&mut self, difficulty: Difficulty, seconds: u64) -> bool {
+        match self.best.get(&difficulty) {
+            Some(&best) if best <= seconds => false,
+            _ => {
+                self.best.insert(difficulty, seconds);
+                true
+            }
+        }
+    }
+
+    pub fn best(&self, difficulty: Difficulty) -> Option<u64> {
+        self.best.get(&difficulty).copied()
+    }
+
+    pub fn save_json<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        if let Some(parent) = path.as_ref().parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = File::create(path)?;
+        serde_json::to_writer(BufWriter::new(file), self)?;
+        Ok(())
+    }
+
+    pub fn load_json<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = File::open(path)?;
+        Ok(serde_json::from_reader(BufReader::new(file))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::TempFile;
+
+    #[test]
+    fn record_keeps_the_fastest_time() {
+        let mut scores = Scores::default();
+
+        assert!(scores.record(Difficulty::Beginner, 60));
+        assert!(!scores.record(Difficulty::Beginner, 75));
+        assert!(!scores.record(Difficulty::Beginner, 60));
+        assert!(scores.record(Difficulty::Beginner, 42));
+        assert!(scores.record(Difficulty::Expert, 300));
+
+        assert_eq!(scores.best(Difficulty::Beginner), Some(42));
+        assert_eq!(scores.best(Difficulty::Expert), Some(300));
+        assert_eq!(scores.best(Difficulty::Intermediate), None);
+    }
+
+    #[test]
+    fn round_trips_through_a_file() {
+        let mut scores = Scores::default();
+        scores.record(Difficulty::Beginner, 42);
+        scores.record(Difficulty::Intermediate, 120);
+
+        let file = TempFile::new("scores");
+        scores.save_json(&file.0).unwrap();
+        assert_eq!(Scores::load_json(&file.0).unwrap(), scores);
+    }
+}
@@ -0,0 +1,469 @@
+use crate::{is_flagged, is_revealed, Board, CellState, FloodStyle, GameState, Marker};
+use alloc::vec;
+use alloc::vec::Vec;
+use rand::Rng;
+
+const SOLVABLE_ATTEMPTS: u64 = 100;
+
+impl Board {
+    // Generates boards until one can be cleared by `solve_step` alone, starting from a reveal in
+    // the middle of the board. The returned board has that opening already revealed; the flag is
+    // false if no guess-free board was found within the retry cap.
+    pub fn new_solvable(width: usize, height: usize, mines: usize, seed: u64) -> (Self, bool) {
+        let start = (height / 2, width / 2);
+
+        for attempt in 0..SOLVABLE_ATTEMPTS {
+            let attempt_seed = seed.wrapping_add(attempt);
+
+            let mut board = Self::new_seeded(width, height, mines, attempt_seed);
+            if board.is_solvable_from(start) {
+                board.reveal_cell(start.0, start.1);
+                return (board, true);
+            }
+        }
+
+        let mut board = Self::new_seeded(
+            width,
+            height,
+            mines,
+            seed.wrapping_add(SOLVABLE_ATTEMPTS - 1),
+        );
+        board.reveal_cell(start.0, start.1);
+        (board, false)
+    }
+
+    // Whether revealing `start` and then following `solve_step` wins the game, without guessing.
+    // Works on a copy, so the board itself is left as it was.
+    pub fn is_solvable_from(&self, start: (usize, usize)) -> bool {
+        let mut simulation = self.clone();
+        simulation.reveal_cell(start.0, start.1);
+        while simulation.apply_deductions() {}
+
+        simulation.state() == GameState::Won
+    }
+
+    // Applies single-point deductions to every revealed number: if its flags already account for
+    // all of its mines the remaining neighbours are safe, and if its hidden neighbours are exactly
+    // its mines they can all be flagged. Returns whether anything changed.
+    pub fn solve_step(&mut self) -> bool {
+        let before = self.snapshot();
+        let changed = self.apply_deductions();
+        self.record_undo(before);
+        changed
+    }
+
+    fn apply_deductions(&mut self) -> bool {
+        if self.state != GameState::Playing {
+            return false;
+        }
+
+        let mut changed = false;
+
+        for row in 0..self.height() {
+            for col in 0..self.width() {
+                let count = match self.grid[self.index(row, col)] {
+                    CellState::Neighbours(true, _, count) => count as usize,
+                    _ => continue,
+                };
+
+                let hidden: Vec<(usize, usize)> = self
+                    .neighbours(row, col)
+                    .filter(|&(o_row, o_col)| !is_revealed(&self.grid[self.index(o_row, o_col)]))
+                    .collect();
+                let unflagged: Vec<(usize, usize)> = hidden
+                    .iter()
+                    .copied()
+                    .filter(|&(o_row, o_col)| !is_flagged(&self.grid[self.index(o_row, o_col)]))
+                    .collect();
+                let flagged = hidden.len() - unflagged.len();
+
+                if unflagged.is_empty() {
+                    continue;
+                }
+
+                if count == flagged {
+                    for (o_row, o_col) in unflagged {
+                        self.reveal_cell(o_row, o_col);
+                    }
+                    changed = true;
+                } else if count == hidden.len() {
+                    for (o_row, o_col) in unflagged {
+                        self.set_marker(o_row, o_col, Marker::Flag);
+                    }
+                    changed = true;
+                }
+
+                if self.state != GameState::Playing {
+                    return changed;
+                }
+            }
+        }
+
+        changed
+    }
+
+    // Whether `solve_step` is stuck while safe cells are still hidden, so the player has to guess.
+    pub fn has_forced_guess(&self) -> bool {
+        if self.state != GameState::Playing || !self.mines_placed {
+            return false;
+        }
+
+        let mut simulation = self.clone();
+        !simulation.apply_deductions() && simulation.count_remaining_safe() > 0
+    }
+
+    // Picks the unrevealed, unflagged cell least likely to be a mine according to
+    // `mine_probability`, preferring the first in row-major order on ties.
+    pub fn safest_unrevealed(&self) -> Option<(usize, usize)> {
+        if self.state != GameState::Playing {
+            return None;
+        }
+
+        let mut safest: Option<((usize, usize), f32)> = None;
+
+        for row in 0..self.height() {
+            for col in 0..self.width() {
+                let cell = &self.grid[self.index(row, col)];
+                if is_revealed(cell) || is_flagged(cell) {
+                    continue;
+                }
+
+                let probability = self.mine_probability(row, col);
+                let is_safer = match safest {
+                    Some((_, best)) => probability < best,
+                    None => true,
+                };
+                if is_safer {
+                    safest = Some(((row, col), probability));
+                }
+            }
+        }
+
+        safest.map(|(coords, _)| coords)
+    }
+
+    // The `mine_probability` of every hidden, unflagged cell next to a revealed number, in
+    // row-major order.
+    pub fn frontier_probabilities(&self) -> Vec<((usize, usize), f32)> {
+        let mut probabilities = Vec::new();
+
+        for row in 0..self.height() {
+            for col in 0..self.width() {
+                let cell = &self.grid[self.index(row, col)];
+                if is_revealed(cell) || is_flagged(cell) {
+                    continue;
+                }
+
+                let on_frontier = self.neighbours(row, col).any(|(n_row, n_col)| {
+                    matches!(
+                        self.grid[self.index(n_row, n_col)],
+                        CellState::Neighbours(true, _, _)
+                    )
+                });
+                if on_frontier {
+                    probabilities.push(((row, col), self.mine_probability(row, col)));
+                }
+            }
+        }
+
+        probabilities
+    }
+
+    // Reveals a hidden, unflagged cell that's certainly not a mine, chosen uniformly, for a player
+    // who's stuck. Returns the cell, or `None` if there are none left.
+    pub fn reveal_random_safe<R: Rng + ?Sized>(&mut self, rng: &mut R) -> Option<(usize, usize)> {
+        if self.state != GameState::Playing {
+            return None;
+        }
+
+        let safe: Vec<(usize, usize)> = self
+            .iter_cells()
+            .filter(|(_, _, cell)| {
+                matches!(cell, CellState::Neighbours(false, marker, _) if *marker != Marker::Flag)
+            })
+            .map(|(row, col, _)| (row, col))
+            .collect();
+        if safe.is_empty() {
+            return None;
+        }
+
+        let (row, col) = safe[rng.gen_range(0, safe.len())];
+        self.reveal_cell(row, col);
+        Some((row, col))
+    }
+
+    // Picks a hidden zero to reveal, preferring the one whose flood opens the most cells and then
+    // the first in row-major order, and falls back to `safest_unrevealed` when there isn't one.
+    // Before the first reveal every cell is safe to open, so the centre is as good as any.
+    pub fn best_opening(&self) -> Option<(usize, usize)> {
+        if self.state != GameState::Playing {
+            return None;
+        }
+
+        if !self.mines_placed {
+            return Some((self.height() / 2, self.width() / 2));
+        }
+
+        let mut best: Option<((usize, usize), usize)> = None;
+
+        for row in 0..self.height() {
+            for col in 0..self.width() {
+                match self.grid[self.index(row, col)] {
+                    CellState::Neighbours(false, Marker::None, 0) => {}
+                    _ => continue,
+                }
+
+                let size = self.flood_size(row, col);
+                // Only a strictly larger flood replaces the best so far, so ties keep the earlier
+                // cell and the choice doesn't depend on anything but the board.
+                let is_larger = match best {
+                    Some((_, largest)) => size > largest,
+                    None => true,
+                };
+                if is_larger {
+                    best = Some(((row, col), size));
+                }
+            }
+        }
+
+        best.map(|(coords, _)| coords)
+            .or_else(|| self.safest_unrevealed())
+    }
+
+    // The number of hidden cells revealing the zero at (row, col) would open, following the
+    // board's flood style.
+    fn flood_size(&self, row: usize, col: usize) -> usize {
+        let mut seen = vec![false; self.grid.len()];
+        let mut stack = vec![(row, col)];
+        let mut size = 0;
+        seen[self.index(row, col)] = true;
+
+        while let Some((row, col)) = stack.pop() {
+            size += 1;
+
+            if let CellState::Neighbours(_, _, 0) = self.grid[self.index(row, col)] {
+                for (o_row, o_col) in self.neighbours(row, col) {
+                    let index = self.index(o_row, o_col);
+                    let cell = &self.grid[index];
+                    let opens = match cell {
+                        CellState::Neighbours(_, _, 0) => true,
+                        _ => self.flood_style == FloodStyle::IncludeBorder,
+                    };
+                    if opens && !seen[index] && !is_revealed(cell) && !is_flagged(cell) {
+                        seen[index] = true;
+                        stack.push((o_row, o_col));
+                    }
+                }
+            }
+        }
+
+        size
+    }
+
+    // A local estimate of the chance an unrevealed cell is a mine. Each revealed neighbouring
+    // number spreads its unflagged mines over its hidden neighbours and the most pessimistic one
+    // wins, unless one of them proves the cell safe. Cells away from the frontier get the density
+    // of the mines left over the whole board.
+    fn mine_probability(&self, row: usize, col: usize) -> f32 {
+        let mut estimate: Option<f32> = None;
+
+        for (n_row, n_col) in self.neighbours(row, col) {
+            let count = match self.grid[self.index(n_row, n_col)] {
+                CellState::Neighbours(true, _, count) => count as usize,
+                _ => continue,
+            };
+
+            let mut flagged = 0;
+            let mut hidden = 0;
+            for (o_row, o_col) in self.neighbours(n_row, n_col) {
+                let cell = &self.grid[self.index(o_row, o_col)];
+                if is_flagged(cell) {
+                    flagged += 1;
+                } else if !is_revealed(cell) {
+                    hidden += 1;
+                }
+            }
+
+            let local = (count.saturating_sub(flagged) as f32 / hidden as f32).min(1.0);
+            if local == 0.0 {
+                return 0.0;
+            }
+            estimate = Some(estimate.map_or(local, |e| e.max(local)));
+        }
+
+        estimate.unwrap_or_else(|| self.remaining_mine_density())
+    }
+
+    fn remaining_mine_density(&self) -> f32 {
+        let hidden = self
+            .grid
+            .iter()
+            .filter(|cell| !is_revealed(cell) && !is_flagged(cell))
+            .count();
+
+        if hidden == 0 {
+            return 0.0;
+        }
+
+        self.remaining_mines_clamped() as f32 / hidden as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::board;
+    use crate::{Board, CellState, FloodStyle, GameState, Marker, RevealOutcome};
+    use alloc::vec;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn solve_step_reveals_around_satisfied_numbers() {
+        let mut board = board(&["*..."]);
+        board.reveal_cell(0, 1);
+        board.flag_cell(0, 0);
+
+        assert!(board.solve_step());
+        assert!(matches!(board.cell(0, 2), CellState::Neighbours(true, ..)));
+        assert_eq!(board.state(), GameState::Won);
+    }
+
+    #[test]
+    fn solve_step_flags_forced_mines() {
+        let mut board = board(&["*..*."]);
+        board.reveal_cell(0, 1);
+        board.reveal_cell(0, 2);
+
+        assert!(board.solve_step());
+        assert_eq!(board.cell(0, 0), CellState::Mine(false, Marker::Flag));
+        assert_eq!(board.cell(0, 3), CellState::Mine(false, Marker::Flag));
+        assert_eq!(board.state(), GameState::Playing);
+        assert!(!board.solve_step());
+    }
+
+    #[test]
+    fn new_solvable_can_be_cleared_without_guessing() {
+        let (mut board, solvable) = Board::new_solvable(9, 9, 10, 3);
+        assert!(solvable);
+        assert!(matches!(board.cell(4, 4), CellState::Neighbours(true, ..)));
+
+        while board.solve_step() {}
+        assert_eq!(board.state(), GameState::Won);
+    }
+
+    #[test]
+    fn safest_unrevealed_prefers_a_proven_safe_cell() {
+        let mut board = board(&[".*..*"]);
+        board.reveal_cell(0, 2);
+        board.flag_cell(0, 1);
+
+        assert_eq!(board.safest_unrevealed(), Some((0, 3)));
+    }
+
+    #[test]
+    fn best_opening_picks_a_zero_that_floods() {
+        let mut checked = 0;
+        for seed in 0..10 {
+            let mut board = Board::new_seeded(30, 16, 99, seed);
+            board.reveal_cell(0, 0);
+            let hidden_zero = CellState::Neighbours(false, Marker::None, 0);
+            if !board.iter_cells().any(|(_, _, cell)| cell == hidden_zero) {
+                continue;
+            }
+
+            let (row, col) = board.best_opening().unwrap();
+            assert_eq!(board.cell(row, col), hidden_zero);
+            match board.reveal_cell(row, col) {
+                RevealOutcome::Revealed(cells) => assert!(cells.len() > 1),
+                outcome => panic!("unexpected {:?}", outcome),
+            }
+            checked += 1;
+        }
+        assert!(checked > 0);
+    }
+
+    #[test]
+    fn forced_guess_on_a_fifty_fifty() {
+        // Neither 1 can tell which of the two cells below is the mine.
+        let mut guess = board(&["..", "..", "*."]);
+        guess.reveal_cell(0, 0);
+        assert!(guess.has_forced_guess());
+
+        let mut solvable = board(&["*..."]);
+        solvable.reveal_cell(0, 1);
+        solvable.flag_cell(0, 0);
+        assert!(!solvable.has_forced_guess());
+    }
+
+    #[test]
+    fn frontier_probabilities_at_the_extremes() {
+        // The 2 has only two hidden neighbours, so both have to be mines.
+        let mut certain = board(&["*.*.."]);
+        certain.reveal_cell(0, 1);
+        assert_eq!(
+            certain.frontier_probabilities(),
+            vec![((0, 0), 1.0), ((0, 2), 1.0)]
+        );
+
+        // Flagging the mine satisfies both numbers, clearing the last hidden cell.
+        let mut cleared = board(&["*.", "..", ".."]);
+        cleared.reveal_cell(2, 1);
+        assert_eq!(
+            cleared.frontier_probabilities(),
+            vec![((0, 0), 0.5), ((0, 1), 0.5)]
+        );
+        cleared.flag_cell(0, 0);
+        assert_eq!(cleared.frontier_probabilities(), vec![((0, 1), 0.0)]);
+    }
+
+    #[test]
+    fn flood_size_follows_the_flood_style() {
+        let layout = ["....*", ".....", "....."];
+        for &(style, size) in &[(FloodStyle::IncludeBorder, 14), (FloodStyle::ZeroOnly, 11)] {
+            let mut flooded = board(&layout).with_flood_style(style);
+            assert_eq!(flooded.flood_size(2, 0), size);
+
+            flooded.reveal_cell(2, 0);
+            assert_eq!(flooded.revealed_count(), size);
+        }
+    }
+
+    #[test]
+    fn best_opening_breaks_ties_by_row_major_order() {
+        // Every zero, on either side of the mines, opens the same four cells.
+        let mirrored = board(&["..*..", "..*.."]);
+        assert_eq!(mirrored.best_opening(), Some((0, 0)));
+
+        // A larger flood later on still wins over an earlier one.
+        let uneven = board(&["..*..."]);
+        assert_eq!(uneven.best_opening(), Some((0, 4)));
+    }
+
+    #[test]
+    fn reveal_random_safe_never_picks_a_mine() {
+        let initial = board(&["*.**", "**.*", "*.**"]);
+        for seed in 0..50 {
+            let mut board = initial.clone();
+            let mut rng = StdRng::seed_from_u64(seed);
+
+            let (row, col) = board.reveal_random_safe(&mut rng).unwrap();
+            assert!(matches!(
+                initial.cell(row, col),
+                CellState::Neighbours(false, ..)
+            ));
+            assert!(matches!(
+                board.cell(row, col),
+                CellState::Neighbours(true, ..)
+            ));
+            assert_eq!(board.state(), GameState::Playing);
+        }
+
+        // Flagged cells are left for the player, even safe ones.
+        let mut board = initial.clone();
+        board.flag_cell(0, 1);
+        board.flag_cell(1, 2);
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(board.reveal_random_safe(&mut rng), Some((2, 1)));
+        assert_eq!(board.reveal_random_safe(&mut rng), None);
+    }
+}
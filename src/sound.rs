@@ -0,0 +1,17 @@
+// Hooks for playing sound effects as the game progresses. The crate doesn't pick an audio
+// backend; implement this over rodio or similar and hand it to the UI.
+pub trait SoundSink {
+    fn on_reveal(&mut self) {}
+
+    fn on_flag(&mut self) {}
+
+    fn on_win(&mut self) {}
+
+    fn on_lose(&mut self) {}
+}
+
+// The default sink, which stays quiet.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Silent;
+
+impl SoundSink for Silent {}
@@ -0,0 +1,37 @@
+#![cfg(feature = "std")]
+
+use minesweepe_rs::{cli, Board, GameState};
+
+fn play(board: &mut Board, script: &str) -> String {
+    let mut output = Vec::new();
+    cli::run(board, script.as_bytes(), &mut output).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn scripted_game_is_won() {
+    let mut board = Board::with_mines(3, 3, &[(0, 0)]).unwrap();
+
+    let output = play(&mut board, "f 0 0\nbogus\nreveal 2 2\nr 1 1\n");
+    assert_eq!(board.state(), GameState::Won);
+    assert!(output.contains("unknown command 'bogus'"));
+    assert!(output.trim_end().ends_with("You win!"));
+}
+
+#[test]
+fn scripted_game_is_lost() {
+    let mut board = Board::with_mines(3, 3, &[(0, 0), (2, 2)]).unwrap();
+
+    let output = play(&mut board, "r 1 1\n\nr 0 0\nr 0 1\n");
+    assert_eq!(board.state(), GameState::Lost);
+    assert!(output.trim_end().ends_with("Game over!"));
+}
+
+#[test]
+fn quitting_leaves_the_game_in_progress() {
+    let mut board = Board::with_mines(3, 3, &[(0, 0), (2, 2)]).unwrap();
+
+    play(&mut board, "r 1 1\nq\nr 0 0\n");
+    assert_eq!(board.state(), GameState::Playing);
+    assert_eq!(board.revealed_count(), 1);
+}
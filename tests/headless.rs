@@ -0,0 +1,22 @@
+// Plays through the library alone, so it also runs with `--no-default-features`.
+use minesweepe_rs::{Board, CellState, GameState, RevealOutcome};
+
+#[test]
+fn plays_a_game_without_the_gui() {
+    let mut board = Board::new_seeded(9, 9, 10, 7);
+    assert!(matches!(
+        board.reveal_cell(4, 4),
+        RevealOutcome::Revealed(_)
+    ));
+
+    let safe: Vec<(usize, usize)> = (0..board.height())
+        .flat_map(|row| (0..board.width()).map(move |col| (row, col)))
+        .filter(|&(row, col)| matches!(board.cell(row, col), CellState::Neighbours(..)))
+        .collect();
+    for (row, col) in safe {
+        board.reveal_cell(row, col);
+    }
+
+    assert_eq!(board.state(), GameState::Won);
+    assert_eq!(board.flags_placed(), 10);
+}